@@ -0,0 +1,28 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+fn main() {
+	println!("cargo:rerun-if-changed=build.rs");
+
+	// Generate `brainrot::youtube::proto` from the schema. `protox` parses it in pure Rust, so building with the `proto`
+	// feature doesn't need `protoc` installed.
+	#[cfg(feature = "proto")]
+	{
+		println!("cargo:rerun-if-changed=proto/chat.proto");
+		let descriptors = protox::compile(["proto/chat.proto"], ["proto"]).expect("failed to parse proto/chat.proto");
+		prost_build::Config::new()
+			.compile_fds(descriptors)
+			.expect("failed to generate protobuf types");
+	}
+}
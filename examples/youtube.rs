@@ -28,11 +28,7 @@ async fn main() -> anyhow::Result<()> {
 			..
 		} = c
 		{
-			println!(
-				"{}: {}",
-				message_renderer_base.author_name.unwrap_or_default().simple_text,
-				message.unwrap().runs.into_iter().map(|c| c.to_chat_string()).collect::<String>()
-			);
+			println!("{}: {}", message_renderer_base.author_name.unwrap_or_default().simple_text, message.unwrap().to_chat_string());
 		}
 	}
 	Ok(())
@@ -29,6 +29,7 @@ pub enum MulticastError {
 }
 
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum VariantChat {
 	Twitch(twitch::ChatEvent),
 	YouTube(youtube::Action)
@@ -38,7 +39,7 @@ pin_project! {
 	#[project = VariantStreamProject]
 	enum VariantStream<'a> {
 		Twitch { #[pin] x: crate::twitch::Chat },
-		YouTube { #[pin] x: Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + 'a>> }
+		YouTube { #[pin] x: Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + Send + 'a>> }
 	}
 }
 
@@ -48,10 +49,10 @@ impl<'a> Stream for VariantStream<'a> {
 	fn poll_next(self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Option<Self::Item>> {
 		match self.project() {
 			VariantStreamProject::YouTube { x } => {
-				Poll::Ready(futures_util::ready!(x.poll_next(cx)).map(|x| x.map(|c| VariantChat::YouTube(c)).map_err(MulticastError::YouTubeError)))
+				Poll::Ready(futures_util::ready!(x.poll_next(cx)).map(|x| x.map(VariantChat::YouTube).map_err(MulticastError::YouTubeError)))
 			}
 			VariantStreamProject::Twitch { x } => {
-				Poll::Ready(futures_util::ready!(x.poll_next(cx)).map(|x| x.map(|c| VariantChat::Twitch(c)).map_err(MulticastError::TwitchError)))
+				Poll::Ready(futures_util::ready!(x.poll_next(cx)).map(|x| x.map(VariantChat::Twitch).map_err(MulticastError::TwitchError)))
 			}
 		}
 	}
@@ -63,8 +64,8 @@ impl<'a> From<crate::twitch::Chat> for VariantStream<'a> {
 	}
 }
 
-impl<'a> From<Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + 'a>>> for VariantStream<'a> {
-	fn from(value: Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + 'a>>) -> Self {
+impl<'a> From<Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + Send + 'a>>> for VariantStream<'a> {
+	fn from(value: Pin<Box<dyn Stream<Item = Result<youtube::Action, youtube::Error>> + Send + 'a>>) -> Self {
 		Self::YouTube { x: value }
 	}
 }
@@ -76,11 +77,20 @@ pin_project! {
 	}
 }
 
+impl<'a> Default for Multicast<'a> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
 impl<'a> Multicast<'a> {
 	pub fn new() -> Self {
 		Self { streams: vec![] }
 	}
 
+	// `VariantStream` is only named in the bound, so callers can push anything convertible into it without the type
+	// itself being public.
+	#[allow(private_bounds)]
 	pub fn push<'b: 'a>(&mut self, stream: impl Into<VariantStream<'b>>) {
 		self.streams.push(stream.into());
 	}
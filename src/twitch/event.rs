@@ -14,6 +14,7 @@
 
 use std::{
 	collections::HashMap,
+	fmt,
 	num::{NonZeroU16, NonZeroU32}
 };
 
@@ -81,11 +82,11 @@ pub enum MessageSegment {
 	}
 }
 
-impl ToString for MessageSegment {
-	fn to_string(&self) -> String {
+impl fmt::Display for MessageSegment {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
-			Self::Text { text } => text.to_owned(),
-			Self::Emote { name, .. } => name.to_owned()
+			Self::Text { text } => f.write_str(text),
+			Self::Emote { name, .. } => f.write_str(name)
 		}
 	}
 }
@@ -180,7 +181,7 @@ pub(crate) fn to_chat_event(message: irc::proto::Message) -> Option<ChatEvent> {
 					emotes.push((id.to_owned(), from, to));
 				}
 			}
-			emotes.sort_by(|a, b| a.1.cmp(&b.1));
+			emotes.sort_by_key(|a| a.1);
 
 			let mut segments = Vec::with_capacity(emotes.len());
 			if !emotes.is_empty() {
@@ -0,0 +1,378 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	borrow::Cow,
+	collections::{HashMap, VecDeque},
+	hash::{Hash, Hasher},
+	sync::Arc
+};
+
+use tokio::sync::Mutex;
+use url::Url;
+
+use super::{
+	get_http_client,
+	types::{
+		get_live_chat::{AuthorBadge, LiveChatAuthorBadgeRenderer, MessageRendererBase},
+		Accessibility, AccessibilityData, Icon
+	},
+	util::TracedSend,
+	Error, ImageContainer, Thumbnail
+};
+
+/// The author of a chat item.
+///
+/// Authors are compared & hashed only by their channel ID, so two `Author`s of the same channel are equal even if their
+/// names or avatars differ (e.g. if the author changed them mid-stream). This makes them usable as `HashMap` keys.
+#[derive(Debug, Clone)]
+pub struct Author {
+	/// The ID of the author's channel, e.g. `UCHVXbQzkl3rDfsXWo8xi2qw`.
+	pub id: String,
+	/// The author's display name. This may be missing for some authors, e.g. those who have deleted their channel.
+	pub name: Option<String>,
+	pub avatar: ImageContainer,
+	pub badges: Vec<Badge>
+}
+
+impl Author {
+	/// Returns a builder for constructing authors by hand, e.g. as test fixtures. See [`AuthorBuilder`].
+	pub fn builder() -> AuthorBuilder {
+		AuthorBuilder::new()
+	}
+
+	/// Returns the URL of the author's channel.
+	pub fn channel_url(&self) -> Url {
+		Url::parse(&format!("https://www.youtube.com/channel/{}", self.id)).unwrap()
+	}
+
+	/// Returns the author's display name with invisible formatting characters (Unicode category `Cf`, e.g. zero-width
+	/// joiners & right-to-left marks) removed, which some users include to create visual glitches. Returns `None` if
+	/// the author has no name.
+	///
+	/// Surrogates (category `Cs`) can't appear in a Rust string in the first place, so they need no handling. Note that
+	/// removing zero-width joiners splits up emoji sequences (like the family emoji) into their individual emoji.
+	pub fn normalized_display_name(&self) -> Option<Cow<'_, str>> {
+		let name = self.name.as_deref()?;
+		Some(if name.chars().any(is_format_char) {
+			Cow::Owned(name.chars().filter(|c| !is_format_char(*c)).collect())
+		} else {
+			Cow::Borrowed(name)
+		})
+	}
+
+	/// Returns the largest version of the author's avatar included in the response, or `None` if the author has no
+	/// avatar.
+	pub fn avatar_image(&self) -> Option<AuthorAvatar> {
+		self.avatar
+			.thumbnails
+			.iter()
+			.max_by_key(|thumbnail| thumbnail.width)
+			.map(|thumbnail| AuthorAvatar(thumbnail.url.clone()))
+	}
+
+	/// Returns a URL to the author's avatar resized to `width`x`width` pixels.
+	///
+	/// YouTube serves avatars at whatever size is requested in the URL's `=s<width>-...` suffix, so this rewrites the
+	/// suffix of the avatar's URL rather than picking from the few sizes included in the response. Returns `None` if
+	/// the author has no avatar.
+	pub fn avatar_at_resolution(&self, width: u32) -> Option<String> {
+		let url = &self.avatar.thumbnails.first()?.url;
+		let base = match url.rfind('=') {
+			Some(suffix_start) if url[suffix_start..].starts_with("=s") => &url[..suffix_start],
+			_ => url
+		};
+		Some(format!("{base}=s{width}-c-k-c0x00ffffff-no-rj"))
+	}
+}
+
+/// Builds [`Author`]s by hand, e.g. for [`ChatItemBuilder::author`](super::ChatItemBuilder::author).
+///
+/// Authors built without an ID or display name get placeholders, and have no avatar or badges unless given.
+///
+/// ```
+/// use brainrot::youtube::{Author, Badge};
+///
+/// let author = Author::builder().id("UC123").display_name("someone").badge(Badge::Moderator).build();
+/// assert_eq!(author.channel_url().as_str(), "https://www.youtube.com/channel/UC123");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AuthorBuilder {
+	id: Option<String>,
+	display_name: Option<String>,
+	avatar_url: Option<String>,
+	badges: Vec<Badge>
+}
+
+impl AuthorBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the ID of the author's channel, e.g. `UCHVXbQzkl3rDfsXWo8xi2qw`.
+	pub fn id(mut self, id: impl Into<String>) -> Self {
+		self.id = Some(id.into());
+		self
+	}
+
+	pub fn display_name(mut self, display_name: impl Into<String>) -> Self {
+		self.display_name = Some(display_name.into());
+		self
+	}
+
+	pub fn avatar_url(mut self, avatar_url: impl Into<String>) -> Self {
+		self.avatar_url = Some(avatar_url.into());
+		self
+	}
+
+	/// Adds a badge to the author. Can be called multiple times.
+	pub fn badge(mut self, badge: Badge) -> Self {
+		self.badges.push(badge);
+		self
+	}
+
+	pub fn build(self) -> Author {
+		Author {
+			id: self.id.unwrap_or_else(|| "UCxxxxxxxxxxxxxxxxxxxxxx".to_string()),
+			name: Some(self.display_name.unwrap_or_else(|| "Viewer".to_string())),
+			avatar: ImageContainer {
+				thumbnails: self
+					.avatar_url
+					.into_iter()
+					.map(|url| Thumbnail { url, width: None, height: None })
+					.collect(),
+				accessibility: None
+			},
+			badges: self.badges
+		}
+	}
+}
+
+/// The URL of an author's avatar image.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthorAvatar(pub String);
+
+impl AuthorAvatar {
+	pub fn url(&self) -> &str {
+		&self.0
+	}
+
+	/// Downloads the avatar image using the crate's HTTP clients.
+	pub async fn fetch(&self) -> Result<Vec<u8>, Error> {
+		self.fetch_with_client(get_http_client()).await
+	}
+
+	/// Downloads the avatar image using `client`.
+	pub async fn fetch_with_client(&self, client: &reqwest::Client) -> Result<Vec<u8>, Error> {
+		// Avatars at a given URL never change (a new avatar gets a new URL), so any cached copy along the way is fine.
+		let response = client
+			.get(&self.0)
+			.header(reqwest::header::ACCEPT, "image/*")
+			.header(reqwest::header::CACHE_CONTROL, "max-stale")
+			.send_traced()
+			.await?
+			.error_for_status()?;
+		Ok(response.bytes().await?.to_vec())
+	}
+}
+
+/// Caches downloaded avatars by author, evicting the least recently used once `capacity` avatars are cached.
+///
+/// In busy streams the same authors show up over and over, so overlays displaying avatars can use this to download
+/// each avatar only once.
+#[derive(Debug)]
+pub struct AvatarCache {
+	capacity: usize,
+	inner: Mutex<AvatarCacheInner>
+}
+
+#[derive(Debug, Default)]
+struct AvatarCacheInner {
+	images: HashMap<String, Arc<Vec<u8>>>,
+	/// Author IDs from least to most recently used.
+	order: VecDeque<String>
+}
+
+impl AvatarCacheInner {
+	fn touch(&mut self, id: &str) {
+		if let Some(position) = self.order.iter().position(|cached| cached == id) {
+			let id = self.order.remove(position).unwrap();
+			self.order.push_back(id);
+		}
+	}
+}
+
+impl AvatarCache {
+	/// # Panics
+	/// Panics if `capacity` is zero.
+	pub fn new(capacity: usize) -> Self {
+		assert!(capacity > 0, "avatar cache must hold at least one avatar");
+		Self {
+			capacity,
+			inner: Mutex::new(AvatarCacheInner::default())
+		}
+	}
+
+	/// Returns the avatar of `author`, downloading it if it isn't cached yet. Returns `Ok(None)` if the author has no
+	/// avatar.
+	///
+	/// The cache isn't locked while downloading, so concurrent calls for the same uncached author may each download
+	/// the avatar.
+	pub async fn get_or_fetch(&self, author: &Author) -> Result<Option<Arc<Vec<u8>>>, Error> {
+		{
+			let mut inner = self.inner.lock().await;
+			if let Some(image) = inner.images.get(&author.id).cloned() {
+				inner.touch(&author.id);
+				return Ok(Some(image));
+			}
+		}
+
+		let Some(avatar) = author.avatar_image() else {
+			return Ok(None);
+		};
+		let image = Arc::new(avatar.fetch().await?);
+
+		let mut inner = self.inner.lock().await;
+		if inner.images.insert(author.id.clone(), Arc::clone(&image)).is_some() {
+			inner.touch(&author.id);
+		} else {
+			inner.order.push_back(author.id.clone());
+			if inner.order.len() > self.capacity {
+				let evicted = inner.order.pop_front().unwrap();
+				inner.images.remove(&evicted);
+			}
+		}
+		Ok(Some(image))
+	}
+
+	/// Returns the number of avatars cached.
+	pub async fn len(&self) -> usize {
+		self.inner.lock().await.images.len()
+	}
+
+	pub async fn is_empty(&self) -> bool {
+		self.inner.lock().await.images.is_empty()
+	}
+}
+
+/// Returns `true` if `c` is in the Unicode `Cf` (format) general category.
+fn is_format_char(c: char) -> bool {
+	matches!(
+		c,
+		'\u{ad}'
+			| '\u{600}'..='\u{605}'
+			| '\u{61c}'
+			| '\u{6dd}'
+			| '\u{70f}'
+			| '\u{890}'..='\u{891}'
+			| '\u{8e2}'
+			| '\u{180e}'
+			| '\u{200b}'..='\u{200f}'
+			| '\u{202a}'..='\u{202e}'
+			| '\u{2060}'..='\u{2064}'
+			| '\u{2066}'..='\u{206f}'
+			| '\u{feff}'
+			| '\u{fff9}'..='\u{fffb}'
+			| '\u{110bd}'
+			| '\u{110cd}'
+			| '\u{13430}'..='\u{1343f}'
+			| '\u{1bca0}'..='\u{1bca3}'
+			| '\u{1d173}'..='\u{1d17a}'
+			| '\u{e0001}'
+			| '\u{e0020}'..='\u{e007f}'
+	)
+}
+
+impl PartialEq for Author {
+	fn eq(&self, other: &Self) -> bool {
+		self.id == other.id
+	}
+}
+
+impl Eq for Author {}
+
+impl Hash for Author {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.id.hash(state);
+	}
+}
+
+impl From<&MessageRendererBase> for Author {
+	fn from(base: &MessageRendererBase) -> Self {
+		Self {
+			id: base.author_external_channel_id.clone(),
+			name: base.author_name.as_ref().map(|name| name.simple_text.clone()),
+			avatar: base.author_photo.clone(),
+			badges: base.author_badges.iter().flatten().map(Badge::from).collect()
+		}
+	}
+}
+
+/// A badge displayed next to an author's name.
+#[derive(Debug, Clone)]
+pub enum Badge {
+	/// The author is the owner of the channel streaming.
+	Owner,
+	Moderator,
+	Verified,
+	/// A channel membership badge. `tooltip` describes the membership, e.g. `Member (6 months)`.
+	Member {
+		tooltip: String,
+		image: ImageContainer
+	},
+	/// A badge this crate doesn't recognize.
+	Other {
+		tooltip: String
+	}
+}
+
+impl From<&Badge> for AuthorBadge {
+	fn from(badge: &Badge) -> Self {
+		let (tooltip, icon, custom_thumbnail) = match badge {
+			Badge::Owner => ("Owner", Some("OWNER"), None),
+			Badge::Moderator => ("Moderator", Some("MODERATOR"), None),
+			Badge::Verified => ("Verified", Some("VERIFIED"), None),
+			Badge::Member { tooltip, image } => (tooltip.as_str(), None, Some(image.clone())),
+			Badge::Other { tooltip } => (tooltip.as_str(), None, None)
+		};
+		AuthorBadge {
+			live_chat_author_badge_renderer: LiveChatAuthorBadgeRenderer {
+				custom_thumbnail,
+				icon: icon.map(|icon_type| Icon { icon_type: icon_type.to_string() }),
+				tooltip: tooltip.to_string(),
+				accessibility: Accessibility {
+					accessibility_data: AccessibilityData { label: tooltip.to_string() }
+				}
+			}
+		}
+	}
+}
+
+impl From<&AuthorBadge> for Badge {
+	fn from(badge: &AuthorBadge) -> Self {
+		let renderer = &badge.live_chat_author_badge_renderer;
+		match badge.icon_type() {
+			Some("OWNER") => Badge::Owner,
+			Some("MODERATOR") => Badge::Moderator,
+			Some("VERIFIED") => Badge::Verified,
+			_ => match &renderer.custom_thumbnail {
+				Some(image) => Badge::Member {
+					tooltip: renderer.tooltip.clone(),
+					image: image.clone()
+				},
+				None => Badge::Other { tooltip: renderer.tooltip.clone() }
+			}
+		}
+	}
+}
@@ -0,0 +1,168 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use simd_json::OwnedValue;
+
+use super::{
+	types::{
+		get_live_chat::{AuthorBadge, ContextMenuEndpoint, LiveChatItemContextMenuEndpoint},
+		Accessibility, AccessibilityData, CommandMetadata
+	},
+	Author, AuthorBuilder, ChatItem, LocalizedRun, LocalizedText, MessageRendererBase, SuperchatTier, UnlocalizedText
+};
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The paid part of a Super Chat built with [`ChatItemBuilder::superchat`].
+#[derive(Debug, Clone)]
+pub struct SuperchatInfo {
+	/// The amount as displayed in chat, e.g. `$5.00`.
+	pub purchase_amount: String,
+	/// Determines the colors of the Super Chat.
+	pub tier: SuperchatTier
+}
+
+impl SuperchatInfo {
+	pub fn new(purchase_amount: impl Into<String>, tier: SuperchatTier) -> Self {
+		Self {
+			purchase_amount: purchase_amount.into(),
+			tier
+		}
+	}
+}
+
+/// Builds [`ChatItem`]s by hand, e.g. as fixtures for testing code that processes chat.
+///
+/// Builds a [`ChatItem::TextMessage`], or a [`ChatItem::Superchat`] if [`superchat`](Self::superchat) is set. Fields
+/// that aren't set get defaults: a unique ID, the placeholder author built by an empty [`AuthorBuilder`], the current
+/// time, and no text. Note that a text message without text fails [`ChatItem::validate`].
+///
+/// ```
+/// use brainrot::youtube::ChatItem;
+///
+/// let item = ChatItem::builder().text("hello world").timestamp_ms(1_708_112_772_000).build();
+/// assert_eq!(item.message().unwrap().to_chat_string(), "hello world");
+/// assert!(item.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChatItemBuilder {
+	id: Option<String>,
+	author: Option<Author>,
+	message: Option<LocalizedText>,
+	timestamp: Option<DateTime<Utc>>,
+	superchat: Option<SuperchatInfo>
+}
+
+impl ChatItemBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn id(mut self, id: impl Into<String>) -> Self {
+		self.id = Some(id.into());
+		self
+	}
+
+	pub fn author(mut self, author: Author) -> Self {
+		self.author = Some(author);
+		self
+	}
+
+	pub fn text(mut self, text: impl Into<String>) -> Self {
+		self.message = Some(LocalizedText::new(vec![LocalizedRun::Text { text: text.into() }]));
+		self
+	}
+
+	/// Sets the message to `message`, which unlike [`text`](Self::text) can include emoji.
+	pub fn message(mut self, message: LocalizedText) -> Self {
+		self.message = Some(message);
+		self
+	}
+
+	/// Sets when the item was sent, in milliseconds since the Unix epoch.
+	///
+	/// # Panics
+	/// Panics if `timestamp_ms` is out of the range [`DateTime`] can represent.
+	pub fn timestamp_ms(mut self, timestamp_ms: i64) -> Self {
+		self.timestamp = Some(DateTime::from_timestamp_millis(timestamp_ms).expect("timestamp out of range"));
+		self
+	}
+
+	/// Sets when the item was sent.
+	pub fn timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+		self.timestamp = Some(timestamp);
+		self
+	}
+
+	/// Makes the item a Super Chat.
+	pub fn superchat(mut self, superchat: SuperchatInfo) -> Self {
+		self.superchat = Some(superchat);
+		self
+	}
+
+	pub fn build(self) -> ChatItem {
+		let author = self.author.unwrap_or_else(|| AuthorBuilder::new().build());
+		let author_badges = (!author.badges.is_empty()).then(|| author.badges.iter().map(AuthorBadge::from).collect());
+		let id = self.id.unwrap_or_else(next_id);
+		let message_renderer_base = MessageRendererBase {
+			author_name: author.name.map(|simple_text| UnlocalizedText { simple_text, accessibility: None }),
+			author_photo: author.avatar,
+			author_badges,
+			context_menu_endpoint: ContextMenuEndpoint {
+				command_metadata: CommandMetadata {
+					web_command_metadata: OwnedValue::default()
+				},
+				live_chat_item_context_menu_endpoint: LiveChatItemContextMenuEndpoint { params: String::new() }
+			},
+			id,
+			timestamp_usec: self.timestamp.unwrap_or_else(Utc::now),
+			author_external_channel_id: author.id,
+			context_menu_accessibility: Accessibility {
+				accessibility_data: AccessibilityData { label: "Chat actions".to_string() }
+			},
+			reply_button: None,
+			context_menu_items: None
+		};
+		let message = self.message;
+		match self.superchat {
+			Some(superchat) => {
+				let (r, g, b) = superchat.tier.color;
+				let color = (0xff << 24) | ((r as isize) << 16) | ((g as isize) << 8) | b as isize;
+				ChatItem::Superchat {
+					message_renderer_base,
+					message,
+					header_sub_text: None,
+					purchase_amount_text: UnlocalizedText {
+						simple_text: superchat.purchase_amount,
+						accessibility: None
+					},
+					header_background_color: color,
+					header_text_color: 0xffffffff,
+					body_background_color: color,
+					body_text_color: 0xffffffff,
+					author_name_text_color: 0xb3ffffff
+				}
+			}
+			None => ChatItem::TextMessage { message_renderer_base, message }
+		}
+	}
+}
+
+/// Gives each built item its own ID, so they aren't mistaken for duplicates of each other.
+fn next_id() -> String {
+	format!("brainrot-builder-{}", NEXT_ID.fetch_add(1, Ordering::Relaxed))
+}
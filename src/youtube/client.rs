@@ -0,0 +1,229 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	fmt,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, OnceLock
+	},
+	time::Duration
+};
+
+use reqwest::{
+	cookie::CookieStore,
+	header::{self, HeaderMap, HeaderValue}
+};
+use url::Url;
+
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:123.0) Gecko/20100101 Firefox/123.0";
+const DEFAULT_BASE_URL: &str = "https://www.youtube.com/";
+
+static CLIENT_POOL: OnceLock<ClientPool> = OnceLock::new();
+
+/// Configuration for the HTTP clients used to talk to YouTube.
+#[derive(Debug, Clone)]
+pub struct YouTubeConfig {
+	user_agent: HeaderValue,
+	timeout: Option<Duration>,
+	cookie_store: Option<SharedCookieStore>,
+	pub(crate) base_url: Url,
+	pub(crate) max_actions_per_page: Option<usize>,
+	pub(crate) max_retries: Option<u32>
+}
+
+impl Default for YouTubeConfig {
+	fn default() -> Self {
+		Self {
+			user_agent: HeaderValue::from_static(DEFAULT_USER_AGENT),
+			timeout: None,
+			cookie_store: None,
+			base_url: Url::parse(DEFAULT_BASE_URL).unwrap(),
+			max_actions_per_page: None,
+			max_retries: None
+		}
+	}
+}
+
+impl YouTubeConfig {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets the `User-Agent` sent with every request. Defaults to a desktop Firefox user agent; YouTube serves
+	/// different pages to other clients, so only change this if you know what you're doing.
+	///
+	/// A user agent that isn't a valid header value (e.g. one containing a newline) is ignored with a warning, keeping
+	/// the previous one.
+	pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+		match HeaderValue::try_from(user_agent.into()) {
+			Ok(user_agent) => self.user_agent = user_agent,
+			Err(e) => tracing::warn!(error = ?e, "invalid user agent; keeping the previous one")
+		}
+		self
+	}
+
+	/// Sets a timeout for each request, from when the request starts connecting until the response body has finished.
+	///
+	/// Note that this also applies to the long-lived Signaler session requests, so it should be comfortably longer than
+	/// the amount of time you'd expect chat to be silent.
+	pub fn with_timeout(mut self, timeout: Duration) -> Self {
+		self.timeout = Some(timeout);
+		self
+	}
+
+	/// Uses `store` to hold the cookies of every client built from this config, instead of each client keeping its own.
+	///
+	/// This lets cookies persist beyond the lifetime of the clients, e.g. to reuse the `CONSENT` & `SOCS` cookies set
+	/// by the consent flow, or to make requests with the cookies of a logged-in session for member-only streams.
+	pub fn with_cookie_store(mut self, store: Arc<dyn CookieStore>) -> Self {
+		self.cookie_store = Some(SharedCookieStore(store));
+		self
+	}
+
+	/// Sets the URL that watch pages & InnerTube endpoints are resolved against, e.g. to point the crate at a mock
+	/// server in tests. Defaults to `https://www.youtube.com/`.
+	///
+	/// Paths are joined onto this URL, so it should end with a `/`. Signaler requests always go to YouTube.
+	pub fn with_base_url(mut self, base_url: Url) -> Self {
+		self.base_url = base_url;
+		self
+	}
+
+	/// Caps the number of actions kept from each page of chat. Very active streams can deliver hundreds of actions in a
+	/// single page; past this limit, the oldest actions in the page are dropped.
+	pub fn with_max_actions_per_page(mut self, max_actions: usize) -> Self {
+		self.max_actions_per_page = Some(max_actions);
+		self
+	}
+
+	/// Caps the number of retries each chat stream may make over its whole lifetime, for streams that don't set their
+	/// own [`StreamOptions::max_total_retries`].
+	///
+	/// [`StreamOptions::max_total_retries`]: super::StreamOptions::max_total_retries
+	pub fn max_retries(mut self, n: u32) -> Self {
+		self.max_retries = Some(n);
+		self
+	}
+
+	pub(crate) fn build_client(&self) -> reqwest::Client {
+		let mut headers = HeaderMap::new();
+		// Set our Accept-Language to en-US so we can properly match substrings
+		headers.append(header::ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.5"));
+		headers.append(header::USER_AGENT, self.user_agent.clone());
+		// Referer is required by Signaler endpoints.
+		headers.append(header::REFERER, HeaderValue::from_static("https://www.youtube.com/"));
+		let mut builder = reqwest::Client::builder().default_headers(headers);
+		// The cookie store keeps the consent cookies YouTube sets after we submit the consent form.
+		builder = match &self.cookie_store {
+			Some(store) => builder.cookie_provider(Arc::new(store.clone())),
+			None => builder.cookie_store(true)
+		};
+		if let Some(timeout) = self.timeout {
+			builder = builder.timeout(timeout);
+		}
+		builder.build().unwrap()
+	}
+}
+
+/// Wraps a `dyn CookieStore` so it can be given to [`reqwest::ClientBuilder::cookie_provider`], which needs a sized
+/// type.
+#[derive(Clone)]
+struct SharedCookieStore(Arc<dyn CookieStore>);
+
+impl CookieStore for SharedCookieStore {
+	fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+		self.0.set_cookies(cookie_headers, url);
+	}
+
+	fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+		self.0.cookies(url)
+	}
+}
+
+impl fmt::Debug for SharedCookieStore {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("SharedCookieStore")
+	}
+}
+
+/// A set of independent HTTP clients which requests are distributed across in a round-robin fashion.
+///
+/// Each [`reqwest::Client`] maintains its own connection pool, so applications monitoring many streams at once can use
+/// a larger pool to spread requests over more connections.
+///
+/// ```no_run
+/// use brainrot::youtube::{ClientPool, YouTubeConfig};
+///
+/// let pool = ClientPool::new(4, YouTubeConfig::default());
+/// pool.install().expect("client pool already initialized");
+/// ```
+#[derive(Debug)]
+pub struct ClientPool {
+	clients: Vec<reqwest::Client>,
+	next: AtomicUsize,
+	config: YouTubeConfig
+}
+
+impl ClientPool {
+	/// Creates a pool of `size` clients built from `config`.
+	///
+	/// # Panics
+	/// Panics if `size` is zero.
+	pub fn new(size: usize, config: YouTubeConfig) -> Self {
+		assert!(size > 0, "client pool must contain at least one client");
+		Self {
+			clients: (0..size).map(|_| config.build_client()).collect(),
+			next: AtomicUsize::new(0),
+			config
+		}
+	}
+
+	/// Returns the next client in the pool.
+	pub fn get(&self) -> &reqwest::Client {
+		&self.clients[self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len()]
+	}
+
+	pub fn len(&self) -> usize {
+		self.clients.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.clients.is_empty()
+	}
+
+	pub fn config(&self) -> &YouTubeConfig {
+		&self.config
+	}
+
+	/// Installs this pool as the one used for all YouTube requests made by this crate.
+	///
+	/// This must be called before any requests are made; once the default pool has been initialized (or another pool
+	/// has been installed), this pool is returned back as an error.
+	// The pool is handed back as-is on failure, like `OnceLock::set`.
+	#[allow(clippy::result_large_err)]
+	pub fn install(self) -> Result<(), Self> {
+		CLIENT_POOL.set(self)
+	}
+}
+
+pub(crate) fn get_client_pool() -> &'static ClientPool {
+	CLIENT_POOL.get_or_init(|| ClientPool::new(1, YouTubeConfig::default()))
+}
+
+/// Returns the config of the installed pool without initializing the default pool, so that code which doesn't make
+/// requests (like parsing a page) doesn't stop a pool from being installed later. `None` means the default config.
+pub(crate) fn installed_config() -> Option<&'static YouTubeConfig> {
+	CLIENT_POOL.get().map(ClientPool::config)
+}
@@ -0,0 +1,56 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{ChatItem, LocalizedRun};
+
+/// A bot command in the form of `!command arg1 arg2`, parsed from a chat message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BotCommand {
+	/// The name of the command, without the prefix.
+	pub name: String,
+	/// The whitespace-separated arguments following the command name. Emojis are included as their chat string, e.g.
+	/// `:face-blue-smiling:`.
+	pub args: Vec<String>
+}
+
+impl BotCommand {
+	/// Parses a command from the message of a text message or superchat.
+	///
+	/// The prefix must be the first non-whitespace character of the message's first run, so messages beginning with an
+	/// emoji (or the prefix appearing anywhere later in the message) are not treated as commands.
+	///
+	/// ```no_run
+	/// # use brainrot::youtube::{BotCommand, ChatItem};
+	/// # fn handle(item: &ChatItem) -> Option<()> {
+	/// let command = BotCommand::parse(item, '!')?;
+	/// println!("{} {:?}", command.name, command.args);
+	/// # Some(())
+	/// # }
+	/// ```
+	pub fn parse(item: &ChatItem, prefix: char) -> Option<Self> {
+		let message = item.message()?;
+		match message.runs().first()? {
+			LocalizedRun::Text { text } if text.trim_start().starts_with(prefix) => {}
+			_ => return None
+		}
+
+		let text = message.to_chat_string();
+		let mut words = text.trim_start()[prefix.len_utf8()..].split_whitespace();
+		let name = words.next()?.to_owned();
+		Some(Self {
+			name,
+			args: words.map(str::to_owned).collect()
+		})
+	}
+}
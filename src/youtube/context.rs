@@ -15,16 +15,131 @@
 use std::sync::OnceLock;
 
 use regex::Regex;
+use reqwest::header;
+use simd_json::{
+	json,
+	prelude::{ValueAsContainer, ValueAsScalar, ValueObjectAccess}
+};
 use url::Url;
 
 use super::{
 	get_http_client,
+	innertube::ClientPlatform,
 	types::streams_page::{
 		FeedContentsRenderer, PageContentsRenderer, RichGridItem, RichItemContent, TabItemRenderer, ThumbnailOverlay, VideoTimeStatus, YouTubeInitialData
 	},
-	Error
+	util::{extract_json_object, SimdJsonRequestBody, TracedSend},
+	youtube_url, Error
 };
 
+const CONSENT_HOST: &str = "consent.youtube.com";
+const CONSENT_SAVE_ENDPOINT: &str = "https://consent.youtube.com/save";
+
+const VISITOR_ID_HEADER: &str = "x-goog-visitor-id";
+
+/// The page YouTube's TV client is served from, which embeds the `TVHTML5` InnerTube config.
+const TV_PAGE: &str = "tv";
+/// YouTube only serves the TV client to TV browsers, like the Cobalt browser in YouTube's TV apps.
+const TV_USER_AGENT: &str = "Mozilla/5.0 (ChromiumStylePlatform) Cobalt/Version";
+const NEXT_ENDPOINT: &str = "youtubei/v1/next";
+
+pub(super) struct PageContents {
+	pub(super) body: String,
+	/// The URL the page was served from, after following redirects.
+	pub(super) url: Url,
+	/// The `X-Goog-Visitor-Id` header YouTube sometimes sends with pages.
+	pub(super) visitor_id: Option<String>
+}
+
+impl PageContents {
+	async fn from_response(response: reqwest::Response) -> Result<Self, Error> {
+		let visitor_id = response
+			.headers()
+			.get(VISITOR_ID_HEADER)
+			.and_then(|value| value.to_str().ok())
+			.map(str::to_owned);
+		Ok(Self {
+			url: response.url().clone(),
+			body: response.text().await?,
+			visitor_id
+		})
+	}
+}
+
+/// Fetches the contents of a YouTube page. If YouTube redirects us to its cookie consent page first (as it does for
+/// most EU IPs), the consent form is submitted and the page is fetched again.
+pub(super) async fn get_page_contents(url: &str) -> Result<PageContents, Error> {
+	get_page_contents_as(url, None).await
+}
+
+/// Like [`get_page_contents`], but overrides the configured `User-Agent` with `user_agent`, for pages only served to
+/// certain clients.
+async fn get_page_contents_as(url: &str, user_agent: Option<&'static str>) -> Result<PageContents, Error> {
+	// Unless a shared cookie store is configured, each client in the pool has its own, so stick to one client for the
+	// whole consent flow.
+	let client = get_http_client();
+	let get = || match user_agent {
+		Some(user_agent) => client.get(url).header(header::USER_AGENT, user_agent),
+		None => client.get(url)
+	};
+	let response = get().send_traced().await?.error_for_status()?;
+	if response.url().host_str() != Some(CONSENT_HOST) {
+		return PageContents::from_response(response).await;
+	}
+
+	submit_consent_form(client, &response.text().await?).await?;
+
+	// The consent cookies should now be in the client's cookie store, so this time we should get the real page.
+	let response = get().send_traced().await?.error_for_status()?;
+	if response.url().host_str() == Some(CONSENT_HOST) {
+		return Err(Error::ConsentRequired);
+	}
+	PageContents::from_response(response).await
+}
+
+async fn submit_consent_form(client: &reqwest::Client, consent_page: &str) -> Result<(), Error> {
+	static CONSENT_FORM_REGEX: OnceLock<Regex> = OnceLock::new();
+	static HIDDEN_INPUT_REGEX: OnceLock<Regex> = OnceLock::new();
+	let form = CONSENT_FORM_REGEX
+		.get_or_init(|| Regex::new(r#"(?s)<form[^>]+action=['"]https://consent\.youtube\.com/save['"][^>]*>(.+?)</form>"#).unwrap())
+		.captures(consent_page)
+		.and_then(|captures| captures.get(1))
+		.ok_or(Error::ConsentRequired)?
+		.as_str();
+	let fields: Vec<(&str, String)> = HIDDEN_INPUT_REGEX
+		.get_or_init(|| Regex::new(r#"<input type=['"]hidden['"] name=['"](.+?)['"] value=['"](.*?)['"]"#).unwrap())
+		.captures_iter(form)
+		.filter_map(|captures| Some((captures.get(1)?.as_str(), captures.get(2)?.as_str().replace("&amp;", "&"))))
+		.collect();
+	// `gl` & `m` are the fields YouTube actually checks; without them, the form is not the one we're looking for.
+	if !["gl", "m"].iter().all(|required| fields.iter().any(|(name, _)| name == required)) {
+		return Err(Error::ConsentRequired);
+	}
+
+	client.post(CONSENT_SAVE_ENDPOINT).form(&fields).send_traced().await?.error_for_status()?;
+	Ok(())
+}
+
+/// Resolves a channel's `@handle` (with or without the `@`) to the ID of its current live stream, using the channel's
+/// `/live` page.
+///
+/// Returns [`Error::NoMatchingStream`] if the channel isn't live (or has no upcoming stream YouTube redirects to).
+pub async fn get_live_video_id_from_handle(handle: &str) -> Result<String, Error> {
+	let handle = handle.strip_prefix('@').unwrap_or(handle);
+	let page = get_page_contents(youtube_url(&format!("@{handle}/live"))?.as_str()).await?;
+	// YouTube either redirects to the stream's watch page, or serves the watch page directly with a canonical link to it.
+	if let Some((_, id)) = page.url.query_pairs().find(|(key, _)| key == "v") {
+		return Ok(id.into_owned());
+	}
+	static CANONICAL_WATCH_REGEX: OnceLock<Regex> = OnceLock::new();
+	CANONICAL_WATCH_REGEX
+		.get_or_init(|| Regex::new(r#"<link rel="canonical" href="https://www\.youtube\.com/watch\?v=([\w-]{11})">"#).unwrap())
+		.captures(&page.body)
+		.and_then(|captures| captures.get(1))
+		.map(|id| id.as_str().to_owned())
+		.ok_or_else(|| Error::NoMatchingStream(format!("@{handle}")))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LiveStreamStatus {
 	Upcoming,
@@ -61,7 +176,35 @@ pub struct ChatContext {
 	pub(crate) client_version: String,
 	pub(crate) initial_continuation: String,
 	pub(crate) tango_api_key: Option<String>,
-	pub(crate) live_status: LiveStreamStatus
+	pub(crate) visitor_data: Option<String>,
+	/// The page's full `INNERTUBE_CONTEXT`, sent verbatim with requests so they carry the same client context (region,
+	/// device, experiment flags, etc.) as the web client.
+	pub(crate) innertube_context: Option<simd_json::OwnedValue>,
+	pub(crate) live_status: LiveStreamStatus,
+	/// The client the config was scraped for, which chat requests claim to be sent from.
+	pub(crate) client_platform: ClientPlatform
+}
+
+/// A placeholder context with an empty video ID & API key, a fixed client version (`2.20230801.08.00`), and a
+/// [`LiveStreamStatus::Replay`] status.
+///
+/// **This is not usable for real requests**; it only exists for tests & code that needs a value to fill in before a
+/// real context is available. Use [`ChatContext::new_from_live`] or [`ChatContext::new_from_channel`] to get a working
+/// context.
+impl Default for ChatContext {
+	fn default() -> Self {
+		Self {
+			id: String::new(),
+			api_key: String::new(),
+			client_version: "2.20230801.08.00".to_string(),
+			initial_continuation: String::new(),
+			tango_api_key: None,
+			visitor_data: None,
+			innertube_context: None,
+			live_status: LiveStreamStatus::Replay,
+			client_platform: ClientPlatform::Web
+		}
+	}
 }
 
 impl ChatContext {
@@ -72,16 +215,16 @@ impl ChatContext {
 		} else {
 			Self::parse_channel_link(channel_id).ok_or_else(|| Error::InvalidChannelID(channel_id.to_string()))?
 		};
-		let page_contents = get_http_client()
-			.get(if channel_id.starts_with('@') {
-				format!("https://www.youtube.com/{channel_id}/streams")
+		let page_contents = get_page_contents(
+			youtube_url(&if channel_id.starts_with('@') {
+				format!("{channel_id}/streams")
 			} else {
-				format!("https://www.youtube.com/channel/{channel_id}/streams")
-			})
-			.send()
-			.await?
-			.text()
-			.await?;
+				format!("channel/{channel_id}/streams")
+			})?
+			.as_str()
+		)
+		.await?
+		.body;
 
 		static YT_INITIAL_DATA_REGEX: OnceLock<Regex> = OnceLock::new();
 		let yt_initial_data: YouTubeInitialData = unsafe {
@@ -154,19 +297,16 @@ impl ChatContext {
 		ChatContext::new_from_live(live_id.ok_or_else(|| Error::NoMatchingStream(channel_id.to_string()))?.0).await
 	}
 
+	/// Creates a context for the current live stream of the channel with the given `@handle`. See
+	/// [`get_live_video_id_from_handle`].
+	pub async fn new_from_handle(handle: impl AsRef<str>) -> Result<ChatContext, Error> {
+		ChatContext::new_from_live(get_live_video_id_from_handle(handle.as_ref()).await?).await
+	}
+
 	pub async fn new_from_live(id: impl AsRef<str>) -> Result<ChatContext, Error> {
-		let id = id.as_ref();
-		let live_id = if id.is_ascii() && id.len() == 11 {
-			id
-		} else {
-			Self::parse_stream_link(id).ok_or_else(|| Error::InvalidVideoID(id.to_string()))?
-		};
-		let page_contents = get_http_client()
-			.get(format!("https://www.youtube.com/watch?v={live_id}"))
-			.send()
-			.await?
-			.text()
-			.await?;
+		let live_id = Self::video_id(id.as_ref())?;
+		let page = get_page_contents(youtube_url(&format!("watch?v={live_id}"))?.as_str()).await?;
+		let page_contents = page.body;
 
 		static LIVE_STREAM_REGEX: OnceLock<Regex> = OnceLock::new();
 		let live_status = if LIVE_STREAM_REGEX
@@ -195,31 +335,7 @@ impl ChatContext {
 			return Err(Error::NotStream(live_id.to_string()));
 		};
 
-		static INNERTUBE_API_KEY_REGEX: OnceLock<Regex> = OnceLock::new();
-		let api_key = match INNERTUBE_API_KEY_REGEX
-			.get_or_init(|| Regex::new(r#"['"]INNERTUBE_API_KEY['"]:\s*['"](.+?)['"]"#).unwrap())
-			.captures(&page_contents)
-			.and_then(|captures| captures.get(1))
-		{
-			Some(matched) => matched.as_str().to_string(),
-			None => return Err(Error::NoInnerTubeKey)
-		};
-
-		static TANGO_API_KEY_REGEX: OnceLock<Regex> = OnceLock::new();
-		let tango_api_key = TANGO_API_KEY_REGEX
-			.get_or_init(|| Regex::new(r#"['"]LIVE_CHAT_BASE_TANGO_CONFIG['"]:\s*\{\s*['"]apiKey['"]\s*:\s*['"](.+?)['"]"#).unwrap())
-			.captures(&page_contents)
-			.and_then(|captures| captures.get(1).map(|c| c.as_str().to_string()));
-
-		static CLIENT_VERSION_REGEX: OnceLock<Regex> = OnceLock::new();
-		let client_version = match CLIENT_VERSION_REGEX
-			.get_or_init(|| Regex::new(r#"['"]clientVersion['"]:\s*['"]([\d.]+?)['"]"#).unwrap())
-			.captures(&page_contents)
-			.and_then(|captures| captures.get(1))
-		{
-			Some(matched) => matched.as_str().to_string(),
-			None => "2.20240207.07.00".to_string()
-		};
+		let config = PageConfig::from_page(&page_contents, page.visitor_id)?;
 
 		static LIVE_CONTINUATION_REGEX: OnceLock<Regex> = OnceLock::new();
 		static REPLAY_CONTINUATION_REGEX: OnceLock<Regex> = OnceLock::new();
@@ -240,14 +356,96 @@ impl ChatContext {
 			None => return Err(Error::NoChatContinuation)
 		};
 
-		Ok(ChatContext {
-			id: live_id.to_string(),
-			api_key,
-			client_version,
-			tango_api_key,
-			initial_continuation: continuation,
-			live_status
-		})
+		Ok(config.into_context(live_id, continuation, live_status, ClientPlatform::Web))
+	}
+
+	/// Creates a context that streams chat as YouTube's TV client (`TVHTML5`), for deployments that only have access to
+	/// YouTube through its TV endpoints.
+	///
+	/// Instead of the watch page, this scrapes the InnerTube config from the TV client's page, then asks the `next`
+	/// endpoint for the video's chat as the TV client. The TV client doesn't say whether a stream is upcoming, so those
+	/// are reported as [`LiveStreamStatus::Live`].
+	pub async fn new_from_tv(id: impl AsRef<str>) -> Result<ChatContext, Error> {
+		let live_id = Self::video_id(id.as_ref())?;
+		let page = get_page_contents_as(youtube_url(TV_PAGE)?.as_str(), Some(TV_USER_AGENT)).await?;
+		let config = PageConfig::from_page(&page.body, page.visitor_id)?;
+
+		let context = config
+			.innertube_context
+			.clone()
+			.unwrap_or_else(|| json!({ "client": { "clientName": ClientPlatform::Tv.client_name(), "clientVersion": config.client_version.as_str() } }));
+		let mut url = youtube_url(NEXT_ENDPOINT)?;
+		url.query_pairs_mut()
+			.append_pair("key", &config.api_key)
+			.append_pair("prettyPrint", "false");
+		let mut request = get_http_client().post(url).header(header::USER_AGENT, TV_USER_AGENT);
+		if let Some(visitor_data) = &config.visitor_data {
+			request = request.header("X-Goog-Visitor-Id", visitor_data);
+		}
+		let request = request.simd_json(&json!({ "context": context, "videoId": live_id }))?;
+		let mut next = request.send_traced().await?.error_for_status()?.bytes().await?.to_vec();
+		let next: simd_json::OwnedValue = simd_json::from_slice(&mut next)?;
+
+		// The TV client nests its chat renderer differently from the web client, and not always in the same place.
+		let chat = find_object(&next, "liveChatRenderer").ok_or_else(|| Error::NotStream(live_id.to_string()))?;
+		let live_status = if chat.get("isReplay").and_then(|replay| replay.as_bool()) == Some(true) {
+			LiveStreamStatus::Replay
+		} else {
+			LiveStreamStatus::Live
+		};
+		// Each continuation is wrapped in an object named for its kind (`reloadContinuationData`,
+		// `timedContinuationData`, ...), which varies between TV client versions.
+		let continuation = chat
+			.get("continuations")
+			.and_then(|continuations| continuations.as_array())
+			.and_then(|continuations| {
+				continuations
+					.iter()
+					.find_map(|continuation| continuation.as_object()?.values().find_map(|data| data.get("continuation")?.as_str()))
+			})
+			.ok_or(Error::NoChatContinuation)?;
+
+		Ok(config.into_context(live_id, continuation.to_string(), live_status, ClientPlatform::Tv))
+	}
+
+	/// Re-fetches the watch page of this context's video to update its InnerTube API key, client version & context in
+	/// place, so long-lived sessions can recover if YouTube rotates them.
+	///
+	/// Note that streams borrow their context, so they must be dropped before the context can be refreshed.
+	pub async fn refresh(&mut self) -> Result<(), Error> {
+		let refreshed = match self.client_platform {
+			ClientPlatform::Tv => Self::new_from_tv(&self.id).await?,
+			_ => Self::new_from_live(&self.id).await?
+		};
+		self.api_key = refreshed.api_key;
+		self.client_version = refreshed.client_version;
+		self.tango_api_key = refreshed.tango_api_key;
+		self.visitor_data = refreshed.visitor_data;
+		self.innertube_context = refreshed.innertube_context;
+		Ok(())
+	}
+
+	/// Finds the web client version in a watch page. The version is usually in the `INNERTUBE_CONTEXT`, but `ytcfg`
+	/// also carries it under a couple of other keys which are tried in turn.
+	fn client_version_from_watch_page(page: &str) -> Option<String> {
+		static CLIENT_VERSION_REGEX: OnceLock<Regex> = OnceLock::new();
+		static CONTEXT_CLIENT_VERSION_REGEX: OnceLock<Regex> = OnceLock::new();
+		[
+			CLIENT_VERSION_REGEX.get_or_init(|| Regex::new(r#"['"]clientVersion['"]:\s*['"]([\d.]+?)['"]"#).unwrap()),
+			CONTEXT_CLIENT_VERSION_REGEX.get_or_init(|| Regex::new(r#"['"]INNERTUBE_(?:CONTEXT_)?CLIENT_VERSION['"]:\s*['"]([\d.]+?)['"]"#).unwrap())
+		]
+		.into_iter()
+		.find_map(|regex| regex.captures(page).and_then(|captures| captures.get(1)))
+		.map(|matched| matched.as_str().to_string())
+	}
+
+	/// Returns the ID of the video `id` is or links to.
+	fn video_id(id: &str) -> Result<&str, Error> {
+		if id.is_ascii() && id.len() == 11 {
+			Ok(id)
+		} else {
+			Self::parse_stream_link(id).ok_or_else(|| Error::InvalidVideoID(id.to_string()))
+		}
 	}
 
 	fn parse_stream_link(url: &str) -> Option<&str> {
@@ -268,15 +466,112 @@ impl ChatContext {
 			.map(|c| c.as_str())
 	}
 
+	/// Returns the ID of the video this context was created for, e.g. `jfKfPfyJRdk`.
 	pub fn id(&self) -> &str {
 		&self.id
 	}
 
+	/// Returns the InnerTube API key scraped from the watch page.
+	pub fn api_key(&self) -> &str {
+		&self.api_key
+	}
+
+	/// Returns the web client version scraped from the watch page, e.g. `2.20240207.07.00`.
+	pub fn client_version(&self) -> &str {
+		&self.client_version
+	}
+
+	/// Returns the continuation token for the first page of chat.
+	pub fn initial_continuation(&self) -> &str {
+		&self.initial_continuation
+	}
+
+	/// Returns the watch page URL of the video this context was created for.
 	pub fn url(&self) -> Url {
 		Url::parse(&format!("https://www.youtube.com/watch?v={}", self.id)).unwrap()
 	}
 
+	/// Returns the status of the stream at the time this context was created.
 	pub fn status(&self) -> LiveStreamStatus {
 		self.live_status
 	}
+
+	/// Returns the client this context's config was scraped for, which chat requests are sent as.
+	pub fn client_platform(&self) -> ClientPlatform {
+		self.client_platform
+	}
+}
+
+/// The InnerTube config a page embeds for the client it's served to.
+struct PageConfig {
+	api_key: String,
+	client_version: String,
+	tango_api_key: Option<String>,
+	visitor_data: Option<String>,
+	innertube_context: Option<simd_json::OwnedValue>
+}
+
+impl PageConfig {
+	fn from_page(page_contents: &str, visitor_id: Option<String>) -> Result<Self, Error> {
+		static INNERTUBE_API_KEY_REGEX: OnceLock<Regex> = OnceLock::new();
+		let api_key = match INNERTUBE_API_KEY_REGEX
+			.get_or_init(|| Regex::new(r#"['"]INNERTUBE_API_KEY['"]:\s*['"](.+?)['"]"#).unwrap())
+			.captures(page_contents)
+			.and_then(|captures| captures.get(1))
+		{
+			Some(matched) => matched.as_str().to_string(),
+			None => return Err(Error::NoInnerTubeKey)
+		};
+
+		static TANGO_API_KEY_REGEX: OnceLock<Regex> = OnceLock::new();
+		let tango_api_key = TANGO_API_KEY_REGEX
+			.get_or_init(|| Regex::new(r#"['"]LIVE_CHAT_BASE_TANGO_CONFIG['"]:\s*\{\s*['"]apiKey['"]\s*:\s*['"](.+?)['"]"#).unwrap())
+			.captures(page_contents)
+			.and_then(|captures| captures.get(1).map(|c| c.as_str().to_string()));
+
+		static VISITOR_DATA_REGEX: OnceLock<Regex> = OnceLock::new();
+		// Prefer the visitor ID header if YouTube sent one, since that's what it expects to see in later requests.
+		let visitor_data = visitor_id.or_else(|| {
+			VISITOR_DATA_REGEX
+				.get_or_init(|| Regex::new(r#"['"](?:VISITOR_DATA|visitorData)['"]:\s*['"](.+?)['"]"#).unwrap())
+				.captures(page_contents)
+				.and_then(|captures| captures.get(1).map(|c| c.as_str().to_string()))
+		});
+
+		let innertube_context = extract_json_object(page_contents, "INNERTUBE_CONTEXT")
+			.and_then(|context| unsafe { simd_json::from_str::<simd_json::OwnedValue>(&mut context.to_owned()) }.ok());
+
+		let client_version = ChatContext::client_version_from_watch_page(page_contents).ok_or(Error::NoClientVersion)?;
+
+		Ok(Self {
+			api_key,
+			client_version,
+			tango_api_key,
+			visitor_data,
+			innertube_context
+		})
+	}
+
+	fn into_context(self, id: &str, initial_continuation: String, live_status: LiveStreamStatus, client_platform: ClientPlatform) -> ChatContext {
+		ChatContext {
+			id: id.to_string(),
+			api_key: self.api_key,
+			client_version: self.client_version,
+			initial_continuation,
+			tango_api_key: self.tango_api_key,
+			visitor_data: self.visitor_data,
+			innertube_context: self.innertube_context,
+			live_status,
+			client_platform
+		}
+	}
+}
+
+/// Finds the first value under `key` in `value` or any object or array nested in it.
+fn find_object<'v>(value: &'v simd_json::OwnedValue, key: &str) -> Option<&'v simd_json::OwnedValue> {
+	if let Some(array) = value.as_array() {
+		return array.iter().find_map(|value| find_object(value, key));
+	}
+	let object = value.as_object()?;
+	object.get(key).or_else(|| object.values().find_map(|value| find_object(value, key)))
 }
@@ -0,0 +1,83 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+
+use super::ChatItem;
+
+/// Flags likely copy-paste spam by comparing each message to the last few messages seen.
+///
+/// Deduplicating by message ID can't catch spam that changes a character or two between messages; this instead flags
+/// messages within `max_distance` edits (Levenshtein distance) of any of the last `window` messages.
+#[derive(Debug, Clone)]
+pub struct FuzzyDedup {
+	max_distance: usize,
+	window: usize,
+	recent: VecDeque<Vec<char>>
+}
+
+impl FuzzyDedup {
+	pub fn new(max_distance: usize, window: usize) -> Self {
+		Self {
+			max_distance,
+			window,
+			recent: VecDeque::with_capacity(window)
+		}
+	}
+
+	/// Returns `true` if `text` is a near-duplicate of a recent message, then records it as the most recent message.
+	pub fn is_duplicate(&mut self, text: &str) -> bool {
+		let text: Vec<char> = text.chars().collect();
+		let duplicate = self.recent.iter().any(|recent| levenshtein_within(recent, &text, self.max_distance));
+		if self.window > 0 {
+			if self.recent.len() == self.window {
+				self.recent.pop_front();
+			}
+			self.recent.push_back(text);
+		}
+		duplicate
+	}
+
+	/// Like [`is_duplicate`](Self::is_duplicate), but for the message of a chat item. Items without a message are never
+	/// considered duplicates.
+	pub fn is_duplicate_item(&mut self, item: &ChatItem) -> bool {
+		match item.message() {
+			Some(message) => self.is_duplicate(&message.to_chat_string()),
+			None => false
+		}
+	}
+}
+
+/// Returns whether the edit distance between `a` & `b` is at most `max`.
+fn levenshtein_within(a: &[char], b: &[char], max: usize) -> bool {
+	if a.len().abs_diff(b.len()) > max {
+		return false;
+	}
+
+	let mut prev: Vec<usize> = (0..=b.len()).collect();
+	let mut cur = vec![0; b.len() + 1];
+	for (i, ca) in a.iter().enumerate() {
+		cur[0] = i + 1;
+		for (j, cb) in b.iter().enumerate() {
+			let substitution = prev[j] + usize::from(ca != cb);
+			cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+		}
+		// every path through the rest of the matrix passes through this row, so stop once all of it is over the limit
+		if cur.iter().all(|&distance| distance > max) {
+			return false;
+		}
+		std::mem::swap(&mut prev, &mut cur);
+	}
+	prev[b.len()] <= max
+}
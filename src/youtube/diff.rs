@@ -0,0 +1,46 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use super::ChatItem;
+
+/// The difference between two pages of chat items, computed by [`diff`].
+#[derive(Debug, Clone, Default)]
+pub struct ChatDiff<'a> {
+	/// Items in the new page that weren't in the old page, in the order they appear in the new page.
+	pub added: Vec<&'a ChatItem>,
+	/// Items in the old page that are no longer in the new page, in the order they appeared in the old page.
+	pub removed: Vec<&'a ChatItem>
+}
+
+impl<'a> ChatDiff<'a> {
+	/// Returns `true` if both pages contained the same items.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty()
+	}
+}
+
+/// Computes which chat items were added & removed between two fetches of the same chat, comparing items by their ID.
+///
+/// Pages fetched with different continuation tokens can overlap; this can be used to display only the new items.
+/// Items without an ID (i.e. [`ChatItem::Unknown`]) can't be compared, and are never included in the diff.
+pub fn diff<'a>(old: &'a [ChatItem], new: &'a [ChatItem]) -> ChatDiff<'a> {
+	let old_ids: HashSet<&str> = old.iter().map(ChatItem::id).filter(|id| !id.is_empty()).collect();
+	let new_ids: HashSet<&str> = new.iter().map(ChatItem::id).filter(|id| !id.is_empty()).collect();
+	ChatDiff {
+		added: new.iter().filter(|item| !item.id().is_empty() && !old_ids.contains(item.id())).collect(),
+		removed: old.iter().filter(|item| !item.id().is_empty() && !new_ids.contains(item.id())).collect()
+	}
+}
@@ -14,8 +14,10 @@
 
 use reqwest::StatusCode;
 use thiserror::Error;
+use url::Url;
 
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
 	#[error("Invalid YouTube video ID or URL: {0}")]
 	InvalidVideoID(String),
@@ -35,30 +37,115 @@ pub enum Error {
 	TimedOut,
 	#[error("request returned bad HTTP status: {0}")]
 	BadStatus(StatusCode),
+	/// YouTube responded with 404 Not Found, usually because the video doesn't exist or has been deleted. Carries the
+	/// ID of the video requested, or the URL of the request if it wasn't for a video.
+	#[error("{0} could not be found")]
+	PageNotFound(String),
+	#[error("rate limited by YouTube")]
+	RateLimited,
+	#[error("YouTube requires authentication for this request")]
+	AuthRequired,
 	#[error("request error: {0}")]
-	GeneralRequest(reqwest::Error),
+	GeneralRequest(#[source] reqwest::Error),
+	#[error("request to {url} failed")]
+	RequestFailed {
+		url: Url,
+		#[source]
+		source: reqwest::Error
+	},
 	#[error("{0} is not a live stream")]
 	NotStream(String),
 	#[error("Failed to match InnerTube API key")]
 	NoInnerTubeKey,
+	/// The watch page didn't contain an InnerTube client version in any of the places it's usually found.
+	#[error("Failed to match InnerTube client version")]
+	NoClientVersion,
+	#[error("InnerTube client is missing required field `{0}`")]
+	MissingClientField(&'static str),
+	/// A stream used up its [`StreamOptions::max_total_retries`]. Carries the number of retries made.
+	///
+	/// [`StreamOptions::max_total_retries`]: super::StreamOptions::max_total_retries
+	#[error("gave up after {0} retries")]
+	MaxRetriesExceeded(u32),
 	#[error("Chat continuation token could not be found.")]
 	NoChatContinuation,
+	#[error("YouTube requested cookie consent, but the consent form could not be submitted.")]
+	ConsentRequired,
 	#[error("Error parsing URL: {0}")]
-	URLParseError(#[from] url::ParseError)
+	URLParseError(#[from] url::ParseError),
+	#[error("I/O error: {0}")]
+	Io(#[from] std::io::Error)
 }
 
 impl Error {
 	pub fn is_fatal(&self) -> bool {
 		!matches!(self, Error::TimedOut)
 	}
+
+	/// Returns `true` if the operation that caused this error may succeed if tried again: timeouts, rate limiting,
+	/// server errors, and requests that failed to connect.
+	pub fn is_retryable(&self) -> bool {
+		match self {
+			Error::TimedOut | Error::RateLimited | Error::RequestFailed { .. } => true,
+			Error::BadStatus(status) => status.is_server_error(),
+			_ => false
+		}
+	}
+
+	/// Returns `true` if YouTube refused the request because it needs to be authenticated, or the session isn't
+	/// allowed to access the resource.
+	pub fn is_auth_required(&self) -> bool {
+		matches!(self, Error::AuthRequired | Error::BadStatus(StatusCode::FORBIDDEN))
+	}
+
+	/// Copies the error, so that a stream can both yield it & report it through [`ChannelStatus::Failed`]. Request,
+	/// deserialization & I/O errors can't be cloned, so their copies only keep the original error's message.
+	///
+	/// [`ChannelStatus::Failed`]: super::ChannelStatus::Failed
+	pub(crate) fn duplicate(&self) -> Self {
+		match self {
+			Error::InvalidVideoID(id) => Error::InvalidVideoID(id.clone()),
+			Error::InvalidChannelID(id) => Error::InvalidChannelID(id.clone()),
+			Error::NoMatchingStream(id) => Error::NoMatchingStream(id.clone()),
+			Error::MissingInitialData => Error::MissingInitialData,
+			Error::Deserialization(e) => Error::Deserialization(simd_json::Error::generic(simd_json::ErrorType::Serde(e.to_string()))),
+			Error::MissingContinuationContents => Error::MissingContinuationContents,
+			Error::EndOfContinuation => Error::EndOfContinuation,
+			Error::TimedOut => Error::TimedOut,
+			Error::BadStatus(status) => Error::BadStatus(*status),
+			Error::PageNotFound(id) => Error::PageNotFound(id.clone()),
+			Error::RateLimited => Error::RateLimited,
+			Error::AuthRequired => Error::AuthRequired,
+			Error::NotStream(id) => Error::NotStream(id.clone()),
+			Error::NoInnerTubeKey => Error::NoInnerTubeKey,
+			Error::NoClientVersion => Error::NoClientVersion,
+			Error::MissingClientField(field) => Error::MissingClientField(field),
+			Error::MaxRetriesExceeded(n) => Error::MaxRetriesExceeded(*n),
+			Error::NoChatContinuation => Error::NoChatContinuation,
+			Error::ConsentRequired => Error::ConsentRequired,
+			Error::URLParseError(e) => Error::URLParseError(*e),
+			Error::Io(e) => Error::Io(std::io::Error::new(e.kind(), e.to_string())),
+			Error::GeneralRequest(_) | Error::RequestFailed { .. } => Error::Io(std::io::Error::other(self.to_string()))
+		}
+	}
 }
 
 impl From<reqwest::Error> for Error {
 	fn from(value: reqwest::Error) -> Self {
 		if value.is_timeout() {
 			Error::TimedOut
+		} else if value.status() == Some(StatusCode::TOO_MANY_REQUESTS) {
+			Error::RateLimited
+		} else if value.status() == Some(StatusCode::UNAUTHORIZED) {
+			Error::AuthRequired
+		} else if value.status() == Some(StatusCode::NOT_FOUND) {
+			let url = value.url();
+			let video_id = url.and_then(|url| url.query_pairs().find(|(key, _)| key == "v").map(|(_, id)| id.into_owned()));
+			Error::PageNotFound(video_id.or_else(|| url.map(Url::to_string)).unwrap_or_default())
 		} else if value.is_status() {
 			Error::BadStatus(value.status().unwrap())
+		} else if let Some(url) = value.url().cloned() {
+			Error::RequestFailed { url, source: value }
 		} else {
 			Error::GeneralRequest(value)
 		}
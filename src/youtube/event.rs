@@ -0,0 +1,254 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{future::ready, pin::Pin};
+
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Deserialize;
+use simd_json::{prelude::*, OwnedValue};
+
+use super::{types::get_live_chat::AuthorBadge, Action, Author, Badge, ChatContext, ChatItem, Error, ImageContainer, LocalizedText, UnlocalizedText};
+
+/// A higher-level view of a chat [`Action`], covering every kind of event this crate understands.
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum ChatEvent {
+	Message {
+		id: String,
+		author: Author,
+		sent_at: DateTime<Utc>,
+		contents: Option<LocalizedText>
+	},
+	SuperChat {
+		id: String,
+		author: Author,
+		sent_at: DateTime<Utc>,
+		/// The formatted amount paid, e.g. `$5.00`.
+		amount: String,
+		contents: Option<LocalizedText>
+	},
+	SuperSticker {
+		id: String,
+		author: Author,
+		sent_at: DateTime<Utc>,
+		/// The formatted amount paid, e.g. `$5.00`.
+		amount: String,
+		sticker: ImageContainer,
+		/// The ID of the message this sticker replaces, if any.
+		///
+		/// Some purchase flows first show a regular text message, which is then replaced by the sticker. In that case
+		/// this is the ID of the original message (which was previously emitted as [`ChatEvent::Message`]), so it can
+		/// be replaced in place instead of appending the sticker as a new message.
+		supersedes: Option<String>
+	},
+	/// A viewer becoming a member, or announcing a membership milestone (with an optional message).
+	Membership {
+		id: String,
+		author: Author,
+		sent_at: DateTime<Utc>,
+		header: Option<LocalizedText>,
+		contents: Option<LocalizedText>
+	},
+	/// A viewer gifting memberships.
+	GiftMembership { id: String, data: simd_json::OwnedValue },
+	/// A viewer redeeming a gifted membership. `gifter_name` is the display name of whoever gifted it, if YouTube
+	/// included it.
+	GiftRedemption { id: String, redeemer: Author, gifter_name: Option<String> },
+	/// A message was deleted by a moderator.
+	MessageDeleted { id: String },
+	/// All messages from a channel were deleted, typically because the author was timed out or banned.
+	AuthorMessagesDeleted { channel_id: String },
+	/// A message was replaced, e.g. when a placeholder is replaced with the actual message.
+	///
+	/// Messages replaced by a Super Sticker are instead emitted as [`ChatEvent::SuperSticker`] with `supersedes` set.
+	MessageReplaced { id: String, replacement: ChatItem },
+	/// The chat's mode changed, e.g. slow mode or members-only mode was turned on or off.
+	ModeChange {
+		id: String,
+		sent_at: DateTime<Utc>,
+		text: LocalizedText,
+		subtext: Option<LocalizedText>
+	},
+	/// A message held for review by YouTube's automated moderation. These are only sent to the channel's owner &
+	/// moderators, who can approve or reject the held message.
+	///
+	/// `inner` is the held message, and `reason` is YouTube's explanation of why it was held, if given.
+	AutoModHeld { id: String, inner: ChatItem, reason: Option<String> },
+	/// An action or chat item this crate doesn't recognize, captured verbatim so it can still be inspected or
+	/// forwarded.
+	///
+	/// The contents are YouTube's raw JSON, whose schema is not stable; new action types may also be given their own
+	/// variant in a future release.
+	Unknown(simd_json::OwnedValue)
+}
+
+impl ChatEvent {
+	/// Converts an action into an event. Returns `None` for recognized actions that don't map to an event, such as
+	/// placeholders or ticker updates.
+	pub fn from_action(action: Action) -> Option<Self> {
+		match action {
+			Action::AddChatItem { item, .. } => Self::from_item(item),
+			Action::RemoveChatItem { target_item_id } => Some(ChatEvent::MessageDeleted { id: target_item_id }),
+			Action::RemoveChatItemByAuthor { external_channel_id } => Some(ChatEvent::AuthorMessagesDeleted { channel_id: external_channel_id }),
+			Action::ReplaceChatItem {
+				target_item_id,
+				replacement_item: replacement_item @ ChatItem::PaidSticker { .. }
+			} => match Self::from_item(replacement_item)? {
+				ChatEvent::SuperSticker {
+					id, author, sent_at, amount, sticker, ..
+				} => Some(ChatEvent::SuperSticker {
+					id,
+					author,
+					sent_at,
+					amount,
+					sticker,
+					supersedes: Some(target_item_id)
+				}),
+				event => Some(event)
+			},
+			Action::ReplaceChatItem { target_item_id, replacement_item } => Some(ChatEvent::MessageReplaced {
+				id: target_item_id,
+				replacement: replacement_item
+			}),
+			Action::Unknown(raw) => Some(ChatEvent::Unknown(raw)),
+			_ => None
+		}
+	}
+
+	/// Returns the raw JSON of [`ChatEvent::Unknown`] events.
+	pub fn raw_action(&self) -> Option<&simd_json::OwnedValue> {
+		match self {
+			ChatEvent::Unknown(raw) => Some(raw),
+			_ => None
+		}
+	}
+
+	fn from_item(item: ChatItem) -> Option<Self> {
+		let author = item.author();
+		match item {
+			ChatItem::TextMessage { message_renderer_base, message } => Some(ChatEvent::Message {
+				id: message_renderer_base.id,
+				author: author?,
+				sent_at: message_renderer_base.timestamp_usec,
+				contents: message
+			}),
+			ChatItem::Superchat {
+				message_renderer_base,
+				message,
+				purchase_amount_text,
+				..
+			} => Some(ChatEvent::SuperChat {
+				id: message_renderer_base.id,
+				author: author?,
+				sent_at: message_renderer_base.timestamp_usec,
+				amount: purchase_amount_text.simple_text,
+				contents: message
+			}),
+			ChatItem::PaidSticker {
+				message_renderer_base,
+				purchase_amount_text,
+				sticker,
+				..
+			} => Some(ChatEvent::SuperSticker {
+				id: message_renderer_base.id,
+				author: author?,
+				sent_at: message_renderer_base.timestamp_usec,
+				amount: purchase_amount_text.simple_text,
+				sticker,
+				supersedes: None
+			}),
+			ChatItem::MembershipItem {
+				message_renderer_base,
+				message,
+				header_primary_text,
+				header_sub_text,
+				..
+			} => Some(ChatEvent::Membership {
+				id: message_renderer_base.id,
+				author: author?,
+				sent_at: message_renderer_base.timestamp_usec,
+				header: header_primary_text.or(header_sub_text),
+				contents: message
+			}),
+			ChatItem::MembershipGift { id, data } => Some(ChatEvent::GiftMembership { id, data }),
+			ChatItem::MembershipGiftRedemption { id, data } => Some(match GiftRedemptionRenderer::parse(&data) {
+				Some(renderer) => ChatEvent::GiftRedemption {
+					id,
+					gifter_name: renderer.gifter_name(),
+					redeemer: renderer.redeemer()
+				},
+				None => ChatEvent::Unknown(data)
+			}),
+			ChatItem::ModeChange {
+				id, timestamp_usec, text, subtext, ..
+			} => Some(ChatEvent::ModeChange {
+				id,
+				sent_at: timestamp_usec,
+				text,
+				subtext
+			}),
+			ChatItem::AutoModMessage { id, auto_moderated_item, header_text } => Some(ChatEvent::AutoModHeld {
+				id,
+				inner: *auto_moderated_item,
+				reason: header_text.as_ref().map(LocalizedText::to_chat_string)
+			}),
+			ChatItem::Unknown(raw) => Some(ChatEvent::Unknown(raw)),
+			_ => None
+		}
+	}
+}
+
+/// The fields of a gift redemption announcement, which YouTube sends with the same author fields as a regular message.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GiftRedemptionRenderer {
+	author_external_channel_id: String,
+	author_name: Option<UnlocalizedText>,
+	author_photo: ImageContainer,
+	author_badges: Option<Vec<AuthorBadge>>,
+	/// e.g. `received a gift membership by <gifter>`, with the gifter's name as its own bold run.
+	message: Option<OwnedValue>
+}
+
+impl GiftRedemptionRenderer {
+	fn parse(data: &OwnedValue) -> Option<Self> {
+		simd_json::serde::from_refowned_value(data).ok()
+	}
+
+	fn redeemer(&self) -> Author {
+		Author {
+			id: self.author_external_channel_id.clone(),
+			name: self.author_name.as_ref().map(|name| name.simple_text.clone()),
+			avatar: self.author_photo.clone(),
+			badges: self.author_badges.iter().flatten().map(Badge::from).collect()
+		}
+	}
+
+	fn gifter_name(&self) -> Option<String> {
+		let runs = self.message.as_ref()?.get_array("runs")?;
+		let run = runs.iter().find(|run| run.get_bool("bold") == Some(true))?;
+		run.get_str("text").map(str::to_owned)
+	}
+}
+
+/// Like [`stream`](super::stream), but yields [`ChatEvent`]s instead of raw actions. Actions that don't map to an
+/// event are skipped.
+pub async fn stream_events(options: &ChatContext) -> Result<Pin<Box<dyn Stream<Item = Result<ChatEvent, Error>> + Send + '_>>, Error> {
+	Ok(Box::pin(
+		super::stream(options)
+			.await?
+			.filter_map(|action| ready(action.map(ChatEvent::from_action).transpose()))
+	))
+}
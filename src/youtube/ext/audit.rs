@@ -0,0 +1,83 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::youtube::{Action, Error};
+
+#[derive(serde::Serialize)]
+struct AuditRecord<'a> {
+	video_id: &'a str,
+	logged_at: DateTime<Utc>,
+	action: &'a Action
+}
+
+#[derive(serde::Serialize)]
+struct AuditLine<'a> {
+	record: &'a AuditRecord<'a>,
+	sha256: String
+}
+
+pub(crate) fn audit_log<'a, S, W>(stream: S, video_id: String, mut writer: W) -> impl Stream<Item = Result<Action, Error>> + 'a
+where
+	S: Stream<Item = Result<Action, Error>> + 'a,
+	W: AsyncWrite + Unpin + Send + 'a
+{
+	let (mut yield_tx, yield_rx) = unsafe { async_stream::__private::yielder::pair() };
+
+	async_stream::__private::AsyncStream::new(yield_rx, async move {
+		let mut stream = std::pin::pin!(stream);
+		while let Some(item) = stream.next().await {
+			let action = match item {
+				Ok(action) => action,
+				Err(e) => {
+					yield_tx.send(Err(e)).await;
+					continue;
+				}
+			};
+			if let Err(e) = write_record(&mut writer, &video_id, &action).await {
+				// An audit log with gaps is no audit log, so stop rather than pass on unlogged actions.
+				yield_tx.send(Err(e)).await;
+				return;
+			}
+			yield_tx.send(Ok(action)).await;
+		}
+		if let Err(e) = writer.flush().await {
+			yield_tx.send(Err(e.into())).await;
+		}
+	})
+}
+
+async fn write_record<W: AsyncWrite + Unpin>(writer: &mut W, video_id: &str, action: &Action) -> Result<(), Error> {
+	let record = AuditRecord {
+		video_id,
+		logged_at: SystemTime::now().into(),
+		action
+	};
+	// `record` serializes the same on its own as it does nested in the line, so the hash covers exactly the bytes of
+	// the line's `record` field.
+	let hash = Sha256::digest(simd_json::to_string(&record)?.as_bytes())
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect();
+	let mut line = simd_json::to_string(&AuditLine { record: &record, sha256: hash })?;
+	line.push('\n');
+	writer.write_all(line.as_bytes()).await?;
+	Ok(())
+}
@@ -0,0 +1,99 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	future::Future,
+	mem,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Sleep};
+
+pin_project! {
+	/// Groups the items of a stream into batches, for overlays that render messages in bulk.
+	///
+	/// A batch is yielded once `window` has passed since its first item arrived, or as soon as it reaches `max_size`
+	/// items, whichever comes first. Any remaining items are yielded as a final batch when the stream ends.
+	pub struct MessageBatcher<S: Stream> {
+		#[pin]
+		stream: S,
+		window: Duration,
+		max_size: usize,
+		items: Vec<S::Item>,
+		sleep: Option<Pin<Box<Sleep>>>,
+		done: bool
+	}
+}
+
+impl<S: Stream> MessageBatcher<S> {
+	/// # Panics
+	/// Panics if `max_size` is zero.
+	pub fn new(stream: S, window: Duration, max_size: usize) -> Self {
+		assert!(max_size > 0, "batches must hold at least one item");
+		Self {
+			stream,
+			window,
+			max_size,
+			items: Vec::new(),
+			sleep: None,
+			done: false
+		}
+	}
+}
+
+impl<S: Stream> Stream for MessageBatcher<S> {
+	type Item = Vec<S::Item>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			if *this.done {
+				return Poll::Ready(if this.items.is_empty() { None } else { Some(mem::take(this.items)) });
+			}
+
+			match this.stream.as_mut().poll_next(cx) {
+				Poll::Ready(Some(item)) => {
+					if this.items.is_empty() {
+						*this.sleep = Some(Box::pin(sleep(*this.window)));
+					}
+					this.items.push(item);
+					if this.items.len() >= *this.max_size {
+						*this.sleep = None;
+						return Poll::Ready(Some(mem::take(this.items)));
+					}
+				}
+				Poll::Ready(None) => *this.done = true,
+				Poll::Pending => {
+					let Some(sleep) = this.sleep.as_mut() else {
+						return Poll::Pending;
+					};
+					futures_util::ready!(sleep.as_mut().poll(cx));
+					*this.sleep = None;
+					return Poll::Ready(Some(mem::take(this.items)));
+				}
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (lower, upper) = self.stream.size_hint();
+		let pending = self.items.len();
+		// Batches cut short by the window can hold as little as one item each.
+		((lower + pending).div_ceil(self.max_size), upper.and_then(|upper| upper.checked_add(pending)))
+	}
+}
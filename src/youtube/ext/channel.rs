@@ -0,0 +1,68 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::pin;
+
+use futures_util::{Stream, StreamExt};
+use tokio::{
+	sync::{
+		broadcast,
+		mpsc::{self, Receiver}
+	},
+	task::JoinHandle
+};
+
+use crate::youtube::Error;
+
+pub(crate) fn to_mpsc<S, T>(stream: S, capacity: usize) -> (Receiver<T>, JoinHandle<Result<(), Error>>)
+where
+	S: Stream<Item = Result<T, Error>> + Send + 'static,
+	T: Send + 'static
+{
+	let (tx, rx) = mpsc::channel(capacity);
+	let handle = tokio::spawn(async move {
+		let mut stream = pin!(stream);
+		while let Some(item) = stream.next().await {
+			// Stop once the receiver is dropped, since nobody is listening anymore.
+			if tx.send(item?).await.is_err() {
+				break;
+			}
+		}
+		Ok(())
+	});
+	(rx, handle)
+}
+
+pub(crate) fn broadcast<S, T>(stream: S, capacity: usize) -> broadcast::Sender<T>
+where
+	S: Stream<Item = Result<T, Error>> + Send + 'static,
+	T: Clone + Send + 'static
+{
+	let (tx, _) = broadcast::channel(capacity);
+	let task_tx = tx.clone();
+	tokio::spawn(async move {
+		let mut stream = pin!(stream);
+		while let Some(item) = stream.next().await {
+			match item {
+				// Sending only fails if there are no receivers right now; more may subscribe later.
+				Ok(item) => drop(task_tx.send(item)),
+				Err(e) => {
+					tracing::warn!("stopping broadcast after stream error: {e}");
+					break;
+				}
+			}
+		}
+	});
+	tx
+}
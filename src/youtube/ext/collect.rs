@@ -0,0 +1,47 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::pin::pin;
+
+use futures_util::{Stream, StreamExt};
+
+use crate::youtube::Error;
+
+pub(crate) async fn try_count<S, T>(stream: S) -> Result<u64, Error>
+where
+	S: Stream<Item = Result<T, Error>>
+{
+	let mut stream = pin!(stream);
+	let mut count = 0;
+	while let Some(item) = stream.next().await {
+		item?;
+		count += 1;
+	}
+	Ok(count)
+}
+
+pub(crate) async fn first_n<S, T>(stream: S, n: usize) -> Result<Vec<T>, Error>
+where
+	S: Stream<Item = Result<T, Error>>
+{
+	let mut stream = pin!(stream);
+	let mut items = Vec::with_capacity(n);
+	while items.len() < n {
+		match stream.next().await {
+			Some(item) => items.push(item?),
+			None => break
+		}
+	}
+	Ok(items)
+}
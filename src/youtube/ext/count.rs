@@ -0,0 +1,84 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::HashMap,
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+
+use crate::youtube::{Action, Error};
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::count_by_author`](super::ChatStreamExt::count_by_author).
+	pub struct CountByAuthor<S> {
+		#[pin]
+		stream: S,
+		emit_every: usize,
+		counts: HashMap<String, u64>,
+		since_last_emit: usize,
+		done: bool
+	}
+}
+
+impl<S> CountByAuthor<S> {
+	pub(crate) fn new(stream: S, emit_every: usize) -> Self {
+		assert!(emit_every > 0, "must emit after at least one message");
+		Self {
+			stream,
+			emit_every,
+			counts: HashMap::new(),
+			since_last_emit: 0,
+			done: false
+		}
+	}
+}
+
+impl<S: Stream<Item = Result<Action, Error>>> Stream for CountByAuthor<S> {
+	type Item = HashMap<String, u64>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			if *this.done {
+				return Poll::Ready(None);
+			}
+
+			match futures_util::ready!(this.stream.as_mut().poll_next(cx)) {
+				Some(Ok(Action::AddChatItem { item, .. })) => {
+					let Some(base) = item.message_renderer_base() else {
+						continue;
+					};
+					*this.counts.entry(base.author_external_channel_id.clone()).or_default() += 1;
+					*this.since_last_emit += 1;
+					if *this.since_last_emit >= *this.emit_every {
+						*this.since_last_emit = 0;
+						return Poll::Ready(Some(this.counts.clone()));
+					}
+				}
+				Some(_) => {}
+				None => {
+					*this.done = true;
+					// Emit a final snapshot so messages since the last one aren't lost.
+					if *this.since_last_emit > 0 {
+						return Poll::Ready(Some(this.counts.clone()));
+					}
+				}
+			}
+		}
+	}
+}
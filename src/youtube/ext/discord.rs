@@ -0,0 +1,192 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{pin::pin, sync::OnceLock, time::Duration};
+
+use futures_util::{Stream, StreamExt};
+use regex::Regex;
+use reqwest::{header, StatusCode};
+use simd_json::{json, prelude::*, OwnedValue};
+use tokio::time::sleep;
+use url::Url;
+
+use crate::youtube::{get_http_client, util::TracedSend, Action, ChatItem, Error, LocalizedRun, LocalizedText};
+
+/// Controls how chat items are formatted when forwarded to a Discord webhook with
+/// [`ChatStreamExt::forward_to_discord_webhook`](super::ChatStreamExt::forward_to_discord_webhook).
+#[derive(Debug, Default, Clone)]
+pub struct DiscordFormat {
+	/// Send Super Chats & Super Stickers as embeds colored like they are in YouTube's chat, instead of plain messages.
+	pub superchat_embeds: bool,
+	/// A mention (e.g. `<@&role id>`) to prepend to new member & membership milestone messages.
+	///
+	/// Only the role & user mentions in this string ping anyone; mentions typed in chat (and `@everyone`/`@here`) are
+	/// never allowed to.
+	pub member_mention: Option<String>,
+	/// Write emoji as their `:shortcode:` instead of as they appear in chat. Custom emoji are always written as
+	/// `:name:`, since Discord has no way to display them.
+	pub emoji_shortcodes: bool
+}
+
+impl DiscordFormat {
+	fn text(&self, text: &LocalizedText) -> String {
+		text.runs()
+			.iter()
+			.map(|run| match run {
+				LocalizedRun::Emoji { emoji, .. } if self.emoji_shortcodes => match emoji.shortcuts.as_ref().and_then(|shortcuts| shortcuts.first()) {
+					Some(shortcut) => shortcut.to_owned(),
+					None => run.to_chat_string()
+				},
+				run => run.to_chat_string()
+			})
+			.collect()
+	}
+
+	/// Builds the webhook payload for `item`, or returns `None` if the item shouldn't be forwarded.
+	fn payload(&self, item: &ChatItem) -> Option<OwnedValue> {
+		let author = item.author()?;
+		let username = author.name.clone().unwrap_or_else(|| author.id.clone());
+		let avatar_url = author.avatar.thumbnails.last().map(|thumbnail| thumbnail.url.clone());
+		let (content, embed) = match item {
+			ChatItem::TextMessage { message, .. } => (message.as_ref().map(|message| self.text(message))?, None),
+			ChatItem::Superchat {
+				message,
+				purchase_amount_text,
+				header_background_color,
+				..
+			} => {
+				let text = message.as_ref().map(|message| self.text(message)).unwrap_or_default();
+				if self.superchat_embeds {
+					(String::new(), Some((purchase_amount_text.simple_text.clone(), text, *header_background_color)))
+				} else {
+					(format!("**{}** {text}", purchase_amount_text.simple_text), None)
+				}
+			}
+			ChatItem::PaidSticker {
+				purchase_amount_text,
+				background_color,
+				..
+			} => {
+				if self.superchat_embeds {
+					(String::new(), Some((purchase_amount_text.simple_text.clone(), String::new(), *background_color)))
+				} else {
+					(format!("**{}** (Super Sticker)", purchase_amount_text.simple_text), None)
+				}
+			}
+			ChatItem::MembershipItem { .. } => {
+				let (_, text) = item.text_with_source()?;
+				let text = self.text(text);
+				let content = match &self.member_mention {
+					Some(mention) => format!("{mention} {text}"),
+					None => text
+				};
+				(content, None)
+			}
+			_ => return None
+		};
+
+		// Chat text is untrusted, so don't let Discord parse mentions out of the content; only ping the roles & users
+		// listed in our own `member_mention`.
+		let (roles, users) = match (item, &self.member_mention) {
+			(ChatItem::MembershipItem { .. }, Some(mention)) => mention_ids(mention),
+			_ => (Vec::new(), Vec::new())
+		};
+		let mut payload = json!({
+			"username": username,
+			"content": content,
+			"allowed_mentions": { "parse": [], "roles": roles, "users": users }
+		});
+		let object = payload.as_object_mut()?;
+		if let Some(avatar_url) = avatar_url {
+			object.insert("avatar_url".to_owned(), avatar_url.into());
+		}
+		if let Some((title, description, color)) = embed {
+			// YouTube's colors are ARGB; Discord wants RGB.
+			let embed = json!({ "title": title, "description": description, "color": color & 0xffffff });
+			object.insert("embeds".to_owned(), json!([embed]));
+		}
+		Some(payload)
+	}
+}
+
+/// An error that stopped a [`forward_to_discord_webhook`](super::ChatStreamExt::forward_to_discord_webhook) call.
+#[derive(Debug, thiserror::Error)]
+pub enum DiscordError {
+	#[error("chat stream error: {0}")]
+	Stream(#[source] Error),
+	#[error("failed to send webhook request: {0}")]
+	Request(#[from] reqwest::Error),
+	#[error("error when serializing webhook payload: {0}")]
+	Serialization(#[from] simd_json::Error)
+}
+
+/// Returns the IDs of the roles & users mentioned in `mention`, e.g. `<@&123>` or `<@456>`.
+fn mention_ids(mention: &str) -> (Vec<String>, Vec<String>) {
+	static MENTION_REGEX: OnceLock<Regex> = OnceLock::new();
+	let (mut roles, mut users) = (Vec::new(), Vec::new());
+	let regex = MENTION_REGEX.get_or_init(|| Regex::new(r"<@(&|!)?(\d+)>").unwrap());
+	for captures in regex.captures_iter(mention) {
+		let id = captures[2].to_owned();
+		match captures.get(1).map(|kind| kind.as_str()) {
+			Some("&") => roles.push(id),
+			_ => users.push(id)
+		}
+	}
+	(roles, users)
+}
+
+/// How many times a rate-limited webhook request is retried before giving up.
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Returns how long Discord asked us to wait before retrying, from the `Retry-After` header (in seconds).
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+	let seconds: f64 = response.headers().get(header::RETRY_AFTER)?.to_str().ok()?.trim().parse().ok()?;
+	Duration::try_from_secs_f64(seconds).ok()
+}
+
+pub(crate) async fn forward<S: Stream<Item = Result<Action, Error>>>(stream: S, webhook_url: Url, format: DiscordFormat) -> Result<(), DiscordError> {
+	let mut stream = pin!(stream);
+	while let Some(action) = stream.next().await {
+		let item = match action {
+			Ok(Action::AddChatItem { item, .. }) => item,
+			Ok(_) => continue,
+			Err(Error::EndOfContinuation) => break,
+			Err(e) => return Err(DiscordError::Stream(e))
+		};
+		let Some(payload) = format.payload(&item) else {
+			continue;
+		};
+		let body = simd_json::to_vec(&payload)?;
+		let mut retries = 0;
+		loop {
+			let response = get_http_client()
+				.post(webhook_url.clone())
+				.header(header::CONTENT_TYPE, "application/json")
+				.body(body.clone())
+				.send_traced()
+				.await?;
+			if response.status() == StatusCode::TOO_MANY_REQUESTS && retries < MAX_RATE_LIMIT_RETRIES {
+				if let Some(delay) = retry_after(&response) {
+					tracing::warn!(?delay, "rate limited by Discord; retrying");
+					sleep(delay).await;
+					retries += 1;
+					continue;
+				}
+			}
+			response.error_for_status()?;
+			break;
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,124 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Instant, Sleep};
+
+/// An item of a [`StreamHealthMonitor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent<T> {
+	/// An item of the underlying stream.
+	Item(T),
+	/// The underlying stream hasn't yielded anything for the contained amount of time, which exceeds the monitor's
+	/// stall threshold.
+	Stalled(Duration),
+	/// The underlying stream yielded an item after stalling. The item itself follows as a [`StreamEvent::Item`].
+	Recovered,
+	/// The underlying stream has finished, e.g. because the live broadcast ended. This is always the last event.
+	Ended
+}
+
+pin_project! {
+	/// Detects when a stream has stalled, i.e. gone longer than a threshold without yielding anything.
+	///
+	/// A stall is reported once with [`StreamEvent::Stalled`], and a [`StreamEvent::Recovered`] is emitted when the
+	/// stream next yields an item. The underlying stream keeps being polled either way, so stalling never ends the
+	/// stream. When the underlying stream finishes, a final [`StreamEvent::Ended`] is emitted, so callers can clean up
+	/// (close log files, announce that the stream is over, etc.) in the same place they handle other events.
+	///
+	/// Since chat streams only yield when a page contains new actions, a quiet chat can look stalled too; choose a
+	/// threshold that's comfortably longer than the chat is expected to go silent.
+	pub struct StreamHealthMonitor<S: Stream> {
+		#[pin]
+		stream: S,
+		stall_threshold: Duration,
+		sleep: Pin<Box<Sleep>>,
+		last_item: Instant,
+		stalled: bool,
+		ended: bool,
+		pending: Option<S::Item>
+	}
+}
+
+impl<S: Stream> StreamHealthMonitor<S> {
+	pub fn new(stream: S, stall_threshold: Duration) -> Self {
+		Self {
+			stream,
+			stall_threshold,
+			sleep: Box::pin(sleep(stall_threshold)),
+			last_item: Instant::now(),
+			stalled: false,
+			ended: false,
+			pending: None
+		}
+	}
+}
+
+impl<S: Stream> Stream for StreamHealthMonitor<S> {
+	type Item = StreamEvent<S::Item>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if let Some(item) = this.pending.take() {
+			return Poll::Ready(Some(StreamEvent::Item(item)));
+		}
+		if *this.ended {
+			return Poll::Ready(None);
+		}
+
+		match this.stream.poll_next(cx) {
+			Poll::Ready(Some(item)) => {
+				*this.last_item = Instant::now();
+				this.sleep.as_mut().reset(*this.last_item + *this.stall_threshold);
+				if *this.stalled {
+					*this.stalled = false;
+					*this.pending = Some(item);
+					Poll::Ready(Some(StreamEvent::Recovered))
+				} else {
+					Poll::Ready(Some(StreamEvent::Item(item)))
+				}
+			}
+			Poll::Ready(None) => {
+				*this.ended = true;
+				Poll::Ready(Some(StreamEvent::Ended))
+			}
+			Poll::Pending => {
+				if *this.stalled {
+					return Poll::Pending;
+				}
+				futures_util::ready!(this.sleep.as_mut().poll(cx));
+				*this.stalled = true;
+				Poll::Ready(Some(StreamEvent::Stalled(this.last_item.elapsed())))
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let pending = usize::from(self.pending.is_some());
+		if self.ended {
+			return (pending, Some(pending));
+		}
+		let (lower, _) = self.stream.size_hint();
+		// Any number of stalls can happen between items, and the stream ends with `StreamEvent::Ended`.
+		(lower + pending + 1, None)
+	}
+}
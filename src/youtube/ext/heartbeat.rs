@@ -0,0 +1,78 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep, Instant, Sleep};
+
+/// An item of an [`InterleaveHeartbeats`] stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeartbeatOrMessage<T> {
+	/// An item of the underlying stream.
+	Message(T),
+	/// No item arrived for a full interval; carries the time the heartbeat was emitted.
+	Heartbeat(Instant)
+}
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::interleave_heartbeats`](super::ChatStreamExt::interleave_heartbeats).
+	pub struct InterleaveHeartbeats<S> {
+		#[pin]
+		stream: S,
+		interval: Duration,
+		sleep: Pin<Box<Sleep>>
+	}
+}
+
+impl<S> InterleaveHeartbeats<S> {
+	pub(crate) fn new(stream: S, interval: Duration) -> Self {
+		Self {
+			stream,
+			interval,
+			sleep: Box::pin(sleep(interval))
+		}
+	}
+}
+
+impl<S: Stream> Stream for InterleaveHeartbeats<S> {
+	type Item = HeartbeatOrMessage<S::Item>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		match this.stream.poll_next(cx) {
+			Poll::Ready(Some(item)) => {
+				this.sleep.as_mut().reset(Instant::now() + *this.interval);
+				Poll::Ready(Some(HeartbeatOrMessage::Message(item)))
+			}
+			Poll::Ready(None) => Poll::Ready(None),
+			Poll::Pending => {
+				futures_util::ready!(this.sleep.as_mut().poll(cx));
+				let now = Instant::now();
+				this.sleep.as_mut().reset(now + *this.interval);
+				Poll::Ready(Some(HeartbeatOrMessage::Heartbeat(now)))
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(self.stream.size_hint().0, None)
+	}
+}
@@ -0,0 +1,274 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Adapters for chat streams.
+
+use std::{future::Future, time::Duration};
+
+use futures_util::Stream;
+use rand::Rng;
+#[cfg(feature = "audit")]
+use tokio::io::AsyncWrite;
+use tokio::{
+	sync::{broadcast, mpsc},
+	task::JoinHandle
+};
+use url::Url;
+
+use super::{Action, Error};
+
+#[cfg(feature = "audit")]
+mod audit;
+mod batch;
+mod channel;
+mod collect;
+mod count;
+mod discord;
+mod health;
+mod heartbeat;
+mod sample;
+mod take_until_end;
+mod throttle;
+mod timestamps;
+
+pub use self::{
+	batch::MessageBatcher,
+	count::CountByAuthor,
+	discord::{DiscordError, DiscordFormat},
+	health::{StreamEvent, StreamHealthMonitor},
+	heartbeat::{HeartbeatOrMessage, InterleaveHeartbeats},
+	sample::Sample,
+	take_until_end::TakeUntilEnd,
+	throttle::Throttle,
+	timestamps::ZipTimestamps
+};
+
+/// Extension methods for chat streams, like those returned by [`stream`](super::stream).
+pub trait ChatStreamExt: Stream {
+	/// Limits the stream to yielding at most `n` items every `per`, for UIs that can't render messages any faster.
+	///
+	/// Items arriving faster than that are buffered and released in later windows. Once the buffer is full (256 items
+	/// by default; see [`Throttle::with_buffer_size`]), further items are dropped with a warning, so a slow consumer
+	/// doesn't fall behind the live feed. Use [`Throttle::with_backpressure`] to stop reading instead.
+	///
+	/// ```no_run
+	/// use std::time::Duration;
+	///
+	/// use brainrot::youtube::{self, ChatStreamExt};
+	/// use futures_util::StreamExt;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> anyhow::Result<()> {
+	/// let context = youtube::ChatContext::new_from_live("jfKfPfyJRdk").await?;
+	/// let stream = youtube::stream(&context).await?.throttle(10, Duration::from_secs(1));
+	/// stream.for_each(|action| async move { println!("{action:?}") }).await;
+	/// # Ok(())
+	/// # }
+	/// ```
+	fn throttle(self, n: usize, per: Duration) -> Throttle<Self>
+	where
+		Self: Sized
+	{
+		Throttle::new(self, n, per)
+	}
+
+	/// Groups items into batches of up to `max_size` items, each collected over at most `window`. See
+	/// [`MessageBatcher`].
+	fn batched(self, window: Duration, max_size: usize) -> MessageBatcher<Self>
+	where
+		Self: Sized
+	{
+		MessageBatcher::new(self, window, max_size)
+	}
+
+	/// Pairs each action of a replay with its offset into the video, unwrapping it from its [`Action::ReplayChat`].
+	/// The stream must be created with [`StreamOptions::replay_offsets`] enabled, since replay actions are flattened
+	/// by default.
+	///
+	/// Actions without an offset are dropped, unless [`ZipTimestamps::with_wall_clock_fallback`] is used.
+	///
+	/// [`StreamOptions::replay_offsets`]: super::StreamOptions::replay_offsets
+	fn zip_timestamps(self) -> ZipTimestamps<Self>
+	where
+		Self: Stream<Item = Result<Action, Error>> + Sized
+	{
+		ZipTimestamps::new(self)
+	}
+
+	/// Posts each message, Super Chat & membership in the stream to a Discord webhook, formatted according to `format`.
+	/// Completes when the stream ends, or with an error if the stream yields one or a webhook request fails.
+	///
+	/// Messages are sent one at a time, under the name & avatar of their author. Mentions typed in chat never ping
+	/// anyone; see [`DiscordFormat::member_mention`]. Note that Discord rate limits webhooks to around 30 messages a
+	/// minute. Rate-limited requests are retried after the delay Discord asks for (up to 5 times), but busy chats
+	/// should still be filtered or [throttled](Self::throttle) first.
+	fn forward_to_discord_webhook(self, webhook_url: Url, format: DiscordFormat) -> impl Future<Output = Result<(), DiscordError>>
+	where
+		Self: Stream<Item = Result<Action, Error>> + Sized
+	{
+		discord::forward(self, webhook_url, format)
+	}
+
+	/// Randomly samples items from the stream, for collecting data from busy streams without processing every item.
+	///
+	/// The first `n` items are always yielded; after that, the `i`th item is yielded with probability `n / i`. This is
+	/// the acceptance rule of reservoir sampling, so keeping the yielded items in a reservoir of `n` (each new item
+	/// replacing a random one) always holds a uniform sample of everything seen so far. The number of items yielded
+	/// grows only logarithmically with the length of the stream.
+	fn sample<R: Rng>(self, n: usize, rng: R) -> Sample<Self, R>
+	where
+		Self: Sized
+	{
+		Sample::new(self, n, rng)
+	}
+
+	/// Reports when the stream goes longer than `stall_threshold` without yielding anything, and when it ends. See
+	/// [`StreamHealthMonitor`].
+	fn monitor_health(self, stall_threshold: Duration) -> StreamHealthMonitor<Self>
+	where
+		Self: Sized
+	{
+		StreamHealthMonitor::new(self, stall_threshold)
+	}
+
+	/// Counts the chat items sent by each author, keyed by channel ID, and yields a snapshot of the counts every
+	/// `emit_every` items, e.g. for live leaderboards. A final snapshot is yielded when the stream ends if any items
+	/// were counted since the last one.
+	///
+	/// Only chat items with an author are counted; other actions & errors are skipped.
+	///
+	/// # Panics
+	/// Panics if `emit_every` is zero.
+	fn count_by_author(self, emit_every: usize) -> CountByAuthor<Self>
+	where
+		Self: Stream<Item = Result<Action, Error>> + Sized
+	{
+		CountByAuthor::new(self, emit_every)
+	}
+
+	/// Yields a [`HeartbeatOrMessage::Heartbeat`] whenever `interval` passes without the stream yielding an item, so
+	/// health checks have a regular signal even when chat is quiet. Items are wrapped in
+	/// [`HeartbeatOrMessage::Message`].
+	fn interleave_heartbeats(self, interval: Duration) -> InterleaveHeartbeats<Self>
+	where
+		Self: Sized
+	{
+		InterleaveHeartbeats::new(self, interval)
+	}
+
+	/// Ends the stream cleanly when it yields [`Error::EndOfContinuation`], instead of passing the error on. All other
+	/// errors are still forwarded.
+	fn take_until_end<T>(self) -> TakeUntilEnd<Self>
+	where
+		Self: Stream<Item = Result<T, Error>> + Sized
+	{
+		TakeUntilEnd::new(self)
+	}
+
+	/// Spawns a task that drains the stream into an [`mpsc`] channel with room for `capacity` items, for applications
+	/// that would rather `recv().await` than poll a stream.
+	///
+	/// The task ends when the stream does, when it yields an error (which the returned [`JoinHandle`] resolves with),
+	/// or once the receiver is dropped. Since the stream is moved onto the task, it must be `'static`; chat streams
+	/// borrow their [`ChatContext`](super::ChatContext), so the context must outlive the task, e.g. by leaking it with
+	/// [`Box::leak`] for the lifetime of the program.
+	///
+	/// ```no_run
+	/// use brainrot::youtube::{self, ChatStreamExt};
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> anyhow::Result<()> {
+	/// let context = Box::leak(Box::new(youtube::ChatContext::new_from_live("jfKfPfyJRdk").await?));
+	/// let (mut actions, task) = youtube::stream(context).await?.to_mpsc(64);
+	/// println!("first action: {:?}", actions.recv().await);
+	/// // Dropping the receiver stops the task.
+	/// drop(actions);
+	/// task.await??;
+	/// # Ok(())
+	/// # }
+	/// ```
+	///
+	/// # Panics
+	/// Panics if `capacity` is zero.
+	fn to_mpsc<T>(self, capacity: usize) -> (mpsc::Receiver<T>, JoinHandle<Result<(), Error>>)
+	where
+		Self: Stream<Item = Result<T, Error>> + Send + Sized + 'static,
+		T: Send + 'static
+	{
+		channel::to_mpsc(self, capacity)
+	}
+
+	/// Spawns a task that forwards every item of the stream to a [`broadcast`] channel holding up to `capacity` items,
+	/// so several consumers (e.g. logging, moderation & an overlay) can share one stream. Call
+	/// [`subscribe`](broadcast::Sender::subscribe) on the returned sender to add a consumer.
+	///
+	/// Items sent while there are no receivers are dropped, and receivers that fall more than `capacity` items behind
+	/// skip ahead, as described in [`broadcast`]. The task ends when the stream does; since errors can't be cloned to
+	/// every receiver, it also ends (with a warning) when the stream yields one. Receivers see the channel close once
+	/// the task has ended and the returned sender (and its clones) are dropped. The stream must be `'static`, as with
+	/// [`to_mpsc`](Self::to_mpsc).
+	///
+	/// # Panics
+	/// Panics if `capacity` is zero.
+	fn broadcast<T>(self, capacity: usize) -> broadcast::Sender<T>
+	where
+		Self: Stream<Item = Result<T, Error>> + Send + Sized + 'static,
+		T: Clone + Send + 'static
+	{
+		channel::broadcast(self, capacity)
+	}
+
+	/// Drains the stream and returns the number of items it yielded, e.g. the total number of actions in a replay.
+	/// Completes with the first error the stream yields instead.
+	///
+	/// Named `try_count` so it doesn't clash with [`StreamExt::count`], which counts errors as items.
+	///
+	/// [`StreamExt::count`]: futures_util::StreamExt::count
+	fn try_count<T>(self) -> impl Future<Output = Result<u64, Error>>
+	where
+		Self: Stream<Item = Result<T, Error>> + Sized
+	{
+		collect::try_count(self)
+	}
+
+	/// Collects the first `n` items of the stream, e.g. to grab a sample of chat for a smoke test. Completes early with
+	/// fewer items if the stream ends first, or with the first error the stream yields.
+	///
+	/// The rest of the stream is dropped once `n` items have been collected, which stops any further requests.
+	fn first_n<T>(self, n: usize) -> impl Future<Output = Result<Vec<T>, Error>>
+	where
+		Self: Stream<Item = Result<T, Error>> + Sized
+	{
+		collect::first_n(self, n)
+	}
+
+	/// Writes an audit record for every action in the stream to `writer` as newline-delimited JSON, passing the actions
+	/// through unchanged. Requires the `audit` feature.
+	///
+	/// Each line has the form `{"record":{...},"sha256":"..."}`, where `record` holds `video_id`, the time the action
+	/// was logged (`logged_at`) & the full `action`, and `sha256` is the hex SHA-256 of `record`'s JSON exactly as it
+	/// appears in the line. The hash only detects accidental corruption: it isn't keyed, so anyone who edits a record
+	/// can simply recompute it. Every action is written before it's yielded, so nothing reaches the consumer without
+	/// being logged; if a write fails, the error is yielded and the stream ends.
+	#[cfg(feature = "audit")]
+	fn audit_log<'a, W>(self, video_id: impl Into<String>, writer: W) -> impl Stream<Item = Result<Action, Error>> + 'a
+	where
+		Self: Stream<Item = Result<Action, Error>> + Sized + 'a,
+		W: AsyncWrite + Unpin + Send + 'a
+	{
+		audit::audit_log(self, video_id.into(), writer)
+	}
+}
+
+impl<S: Stream + ?Sized> ChatStreamExt for S {}
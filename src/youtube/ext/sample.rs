@@ -0,0 +1,62 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+use rand::Rng;
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::sample`](super::ChatStreamExt::sample).
+	pub struct Sample<S, R> {
+		#[pin]
+		stream: S,
+		n: usize,
+		observed: u64,
+		rng: R
+	}
+}
+
+impl<S, R> Sample<S, R> {
+	pub(crate) fn new(stream: S, n: usize, rng: R) -> Self {
+		Self { stream, n, observed: 0, rng }
+	}
+}
+
+impl<S: Stream, R: Rng> Stream for Sample<S, R> {
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			let Some(item) = futures_util::ready!(this.stream.as_mut().poll_next(cx)) else {
+				return Poll::Ready(None);
+			};
+			*this.observed += 1;
+			// The first `n` items are always kept; after that, the `i`th item is kept with probability `n / i`, as in
+			// reservoir sampling.
+			if *this.observed <= *this.n as u64 || this.rng.gen_range(0..*this.observed) < *this.n as u64 {
+				return Poll::Ready(Some(item));
+			}
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		(0, self.stream.size_hint().1)
+	}
+}
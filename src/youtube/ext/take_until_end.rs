@@ -0,0 +1,61 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	pin::Pin,
+	task::{Context, Poll}
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+
+use crate::youtube::Error;
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::take_until_end`](super::ChatStreamExt::take_until_end).
+	pub struct TakeUntilEnd<S> {
+		#[pin]
+		stream: S,
+		done: bool
+	}
+}
+
+impl<S> TakeUntilEnd<S> {
+	pub(crate) fn new(stream: S) -> Self {
+		Self { stream, done: false }
+	}
+}
+
+impl<T, S: Stream<Item = Result<T, Error>>> Stream for TakeUntilEnd<S> {
+	type Item = Result<T, Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.project();
+		if *this.done {
+			return Poll::Ready(None);
+		}
+
+		match futures_util::ready!(this.stream.poll_next(cx)) {
+			Some(Err(Error::EndOfContinuation)) | None => {
+				*this.done = true;
+				Poll::Ready(None)
+			}
+			item => Poll::Ready(item)
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		if self.done { (0, Some(0)) } else { (0, self.stream.size_hint().1) }
+	}
+}
@@ -0,0 +1,135 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::VecDeque,
+	future::Future,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+use tokio::time::{sleep_until, Instant, Sleep};
+
+const DEFAULT_BUFFER_SIZE: usize = 256;
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::throttle`](super::ChatStreamExt::throttle).
+	pub struct Throttle<S: Stream> {
+		#[pin]
+		stream: S,
+		limit: usize,
+		per: Duration,
+		buffer: VecDeque<S::Item>,
+		buffer_size: usize,
+		backpressure: bool,
+		window_start: Option<Instant>,
+		sent_in_window: usize,
+		sleep: Option<Pin<Box<Sleep>>>,
+		done: bool
+	}
+}
+
+impl<S: Stream> Throttle<S> {
+	/// # Panics
+	/// Panics if `n` is zero.
+	pub(crate) fn new(stream: S, n: usize, per: Duration) -> Self {
+		assert!(n > 0, "throttle must allow at least one item per window");
+		Self {
+			stream,
+			limit: n,
+			per,
+			buffer: VecDeque::new(),
+			buffer_size: DEFAULT_BUFFER_SIZE,
+			backpressure: false,
+			window_start: None,
+			sent_in_window: 0,
+			sleep: None,
+			done: false
+		}
+	}
+
+	/// Sets the maximum number of items held back by the throttle. Items that arrive while the buffer is full are
+	/// dropped. Sizes below 1 are treated as 1.
+	pub fn with_buffer_size(mut self, size: usize) -> Self {
+		self.buffer_size = size.max(1);
+		self
+	}
+
+	/// Stops reading from the underlying stream while the buffer is full, instead of dropping items. Nothing is lost,
+	/// but the consumer falls further behind a live stream the longer chat outpaces the throttle.
+	pub fn with_backpressure(mut self) -> Self {
+		self.backpressure = true;
+		self
+	}
+}
+
+impl<S: Stream> Stream for Throttle<S> {
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+
+		// Pull in whatever's ready so the underlying stream keeps making progress while items are held back. Reads are
+		// capped at the buffer size per poll, so a stream that's always ready isn't polled forever.
+		let mut dropped = 0;
+		for _ in 0..*this.buffer_size {
+			if *this.done || (*this.backpressure && this.buffer.len() >= *this.buffer_size) {
+				break;
+			}
+			match this.stream.as_mut().poll_next(cx) {
+				Poll::Ready(Some(_)) if this.buffer.len() >= *this.buffer_size => dropped += 1,
+				Poll::Ready(Some(item)) => this.buffer.push_back(item),
+				Poll::Ready(None) => *this.done = true,
+				Poll::Pending => break
+			}
+		}
+		if dropped > 0 {
+			tracing::warn!(dropped, "throttle buffer is full; dropping items");
+		}
+
+		if this.buffer.is_empty() {
+			return if *this.done { Poll::Ready(None) } else { Poll::Pending };
+		}
+
+		loop {
+			if let Some(sleep) = this.sleep.as_mut() {
+				futures_util::ready!(sleep.as_mut().poll(cx));
+				*this.sleep = None;
+			}
+
+			let now = Instant::now();
+			let window_start = match *this.window_start {
+				Some(start) if now < start + *this.per => start,
+				_ => {
+					*this.sent_in_window = 0;
+					*this.window_start.insert(now)
+				}
+			};
+			if *this.sent_in_window < *this.limit {
+				*this.sent_in_window += 1;
+				return Poll::Ready(this.buffer.pop_front());
+			}
+
+			*this.sleep = Some(Box::pin(sleep_until(window_start + *this.per)));
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (_, upper) = self.stream.size_hint();
+		(self.buffer.len(), upper.and_then(|upper| upper.checked_add(self.buffer.len())))
+	}
+}
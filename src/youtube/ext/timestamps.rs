@@ -0,0 +1,81 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::VecDeque,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::Stream;
+use pin_project_lite::pin_project;
+
+use crate::youtube::{Action, Error};
+
+pin_project! {
+	/// Stream returned by [`ChatStreamExt::zip_timestamps`](super::ChatStreamExt::zip_timestamps).
+	pub struct ZipTimestamps<S> {
+		#[pin]
+		stream: S,
+		wall_clock_fallback: bool,
+		pending: VecDeque<(Duration, Action)>
+	}
+}
+
+impl<S> ZipTimestamps<S> {
+	pub(crate) fn new(stream: S) -> Self {
+		Self {
+			stream,
+			wall_clock_fallback: false,
+			pending: VecDeque::new()
+		}
+	}
+
+	/// Instead of dropping actions that aren't part of a replay, pair chat items with the time they were sent (as a
+	/// duration since the Unix epoch). Actions with no timestamp at all are still dropped.
+	pub fn with_wall_clock_fallback(mut self) -> Self {
+		self.wall_clock_fallback = true;
+		self
+	}
+}
+
+impl<S: Stream<Item = Result<Action, Error>>> Stream for ZipTimestamps<S> {
+	type Item = Result<(Duration, Action), Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let mut this = self.project();
+		loop {
+			if let Some(pending) = this.pending.pop_front() {
+				return Poll::Ready(Some(Ok(pending)));
+			}
+
+			match futures_util::ready!(this.stream.as_mut().poll_next(cx)) {
+				Some(Ok(Action::ReplayChat { actions, video_offset_time_msec })) => {
+					let offset = Duration::from_millis(video_offset_time_msec.max(0) as _);
+					this.pending.extend(actions.into_iter().map(|action| (offset, action.action)));
+				}
+				Some(Ok(Action::AddChatItem { item, client_id })) if *this.wall_clock_fallback => {
+					if let Some(timestamp) = item.timestamp() {
+						let offset = Duration::from_micros(timestamp.timestamp_micros().max(0) as _);
+						return Poll::Ready(Some(Ok((offset, Action::AddChatItem { item, client_id }))));
+					}
+				}
+				Some(Ok(_)) => {}
+				Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+				None => return Poll::Ready(None)
+			}
+		}
+	}
+}
@@ -0,0 +1,92 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Action, ChatItem};
+
+/// The kinds of chat items a [`MessageFilter`] can select.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventType {
+	TextMessage,
+	SuperChat,
+	SuperSticker,
+	/// A new member joining, or a member announcing a membership milestone.
+	Membership,
+	/// A viewer gifting memberships, or receiving a gifted membership.
+	GiftMembership
+}
+
+impl EventType {
+	/// Returns the event type of `item`, or `None` if it isn't one of the types covered by [`EventType`].
+	pub fn of(item: &ChatItem) -> Option<Self> {
+		match item {
+			ChatItem::TextMessage { .. } => Some(EventType::TextMessage),
+			ChatItem::Superchat { .. } => Some(EventType::SuperChat),
+			ChatItem::PaidSticker { .. } => Some(EventType::SuperSticker),
+			ChatItem::MembershipItem { .. } => Some(EventType::Membership),
+			ChatItem::MembershipGift { .. } | ChatItem::MembershipGiftRedemption { .. } => Some(EventType::GiftMembership),
+			_ => None
+		}
+	}
+}
+
+/// Selects which chat items a caller is interested in.
+///
+/// ```no_run
+/// use std::future::ready;
+///
+/// use brainrot::youtube::{self, EventType, MessageFilter};
+/// use futures_util::StreamExt;
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let context = youtube::ChatContext::new_from_live("jfKfPfyJRdk").await?;
+/// let filter = MessageFilter::new().event_type(&[EventType::SuperChat, EventType::SuperSticker]);
+/// let stream = youtube::stream(&context).await?;
+/// let paid_messages = stream.filter(|action| ready(action.as_ref().is_ok_and(|action| filter.matches(action))));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+	event_types: Option<Vec<EventType>>
+}
+
+impl MessageFilter {
+	/// Creates a filter that matches every chat item.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Only match chat items of the given types.
+	pub fn event_type(mut self, types: &[EventType]) -> Self {
+		self.event_types = Some(types.to_vec());
+		self
+	}
+
+	/// Returns whether `item` passes this filter.
+	pub fn matches_item(&self, item: &ChatItem) -> bool {
+		match &self.event_types {
+			Some(types) => EventType::of(item).is_some_and(|ty| types.contains(&ty)),
+			None => true
+		}
+	}
+
+	/// Returns whether `action` adds a chat item that passes this filter. Other actions never match.
+	pub fn matches(&self, action: &Action) -> bool {
+		match action {
+			Action::AddChatItem { item, .. } => self.matches_item(item),
+			_ => false
+		}
+	}
+}
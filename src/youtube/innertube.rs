@@ -0,0 +1,162 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{
+	types::get_live_chat::{GetLiveChatRequestBody, GetLiveChatResponse},
+	Error, TANGO_LIVE_ENDPOINT, TANGO_REPLAY_ENDPOINT
+};
+
+/// The InnerTube client a request claims to be sent from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClientPlatform {
+	/// The desktop web client.
+	#[default]
+	Web,
+	/// The mobile web client (`m.youtube.com`).
+	MobileWeb,
+	/// The smart TV client (`TVHTML5`), used by YouTube's TV apps & `youtube.com/tv`. Use
+	/// [`ChatContext::new_from_tv`](super::ChatContext::new_from_tv) to scrape its config.
+	Tv
+}
+
+impl ClientPlatform {
+	/// Returns the `clientName` InnerTube knows this platform as.
+	pub fn client_name(&self) -> &'static str {
+		match self {
+			Self::Web => "WEB",
+			Self::MobileWeb => "MWEB",
+			Self::Tv => "TVHTML5"
+		}
+	}
+}
+
+/// A client for the InnerTube live chat endpoints, for use when the API key & client version are already known and
+/// there's no need to scrape them from a watch page with [`ChatContext`](super::ChatContext).
+///
+/// ```no_run
+/// # async fn run() -> Result<(), brainrot::youtube::Error> {
+/// use brainrot::youtube::InnerTubeClient;
+///
+/// let builder = InnerTubeClient::builder().api_key("AIza...").hl("en");
+/// let client = builder.client_version("2.20240207.07.00").build()?;
+/// let page = client.fetch_live_chat_page("0ofMyAN...").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InnerTubeClient {
+	api_key: String,
+	client_version: String,
+	client_platform: ClientPlatform,
+	visitor_data: Option<String>,
+	hl: Option<String>,
+	gl: Option<String>
+}
+
+impl InnerTubeClient {
+	pub fn builder() -> InnerTubeClientBuilder {
+		InnerTubeClientBuilder::default()
+	}
+
+	pub fn client_platform(&self) -> ClientPlatform {
+		self.client_platform
+	}
+
+	/// Fetches a page of a live stream's chat.
+	pub async fn fetch_live_chat_page(&self, continuation: impl AsRef<str>) -> Result<GetLiveChatResponse, Error> {
+		self.fetch(TANGO_LIVE_ENDPOINT, continuation.as_ref()).await
+	}
+
+	/// Fetches a page of a finished stream's chat replay.
+	pub async fn fetch_replay_chat_page(&self, continuation: impl AsRef<str>) -> Result<GetLiveChatResponse, Error> {
+		self.fetch(TANGO_REPLAY_ENDPOINT, continuation.as_ref()).await
+	}
+
+	async fn fetch(&self, endpoint: &str, continuation: &str) -> Result<GetLiveChatResponse, Error> {
+		let mut body = GetLiveChatRequestBody::new(continuation, &self.client_version, self.client_platform.client_name());
+		body = body.with_locale(self.hl.clone(), self.gl.clone());
+		if let Some(visitor_data) = &self.visitor_data {
+			body = body.with_visitor_data(visitor_data);
+		}
+		let mut raw = GetLiveChatResponse::post_raw(endpoint, &self.api_key, self.visitor_data.as_deref(), &body).await?;
+		Ok(simd_json::from_slice(&mut raw)?)
+	}
+}
+
+/// Builder for an [`InnerTubeClient`]. An API key & client version are required.
+#[derive(Debug, Default, Clone)]
+pub struct InnerTubeClientBuilder {
+	api_key: Option<String>,
+	client_version: Option<String>,
+	client_platform: ClientPlatform,
+	visitor_data: Option<String>,
+	hl: Option<String>,
+	gl: Option<String>
+}
+
+impl InnerTubeClientBuilder {
+	/// Sets the InnerTube API key (`INNERTUBE_API_KEY`).
+	pub fn api_key(mut self, key: impl Into<String>) -> Self {
+		self.api_key = Some(key.into());
+		self
+	}
+
+	/// Sets the client version (`INNERTUBE_CONTEXT_CLIENT_VERSION`), e.g. `2.20240207.07.00`.
+	pub fn client_version(mut self, version: impl Into<String>) -> Self {
+		self.client_version = Some(version.into());
+		self
+	}
+
+	/// Sets the platform requests claim to be sent from. Defaults to [`ClientPlatform::Web`].
+	pub fn client_platform(mut self, platform: ClientPlatform) -> Self {
+		self.client_platform = platform;
+		self
+	}
+
+	/// Sets the encrypted visitor data identifying the session requests belong to.
+	pub fn visitor_data(mut self, data: impl Into<String>) -> Self {
+		self.visitor_data = Some(data.into());
+		self
+	}
+
+	/// Sets the interface language, e.g. `en`.
+	pub fn hl(mut self, lang: impl Into<String>) -> Self {
+		self.hl = Some(lang.into());
+		self
+	}
+
+	/// Sets the content region, e.g. `US`.
+	pub fn gl(mut self, region: impl Into<String>) -> Self {
+		self.gl = Some(region.into());
+		self
+	}
+
+	/// Builds the client, returning [`Error::MissingClientField`] if the API key or client version weren't set or are
+	/// empty.
+	pub fn build(self) -> Result<InnerTubeClient, Error> {
+		let api_key = self.api_key.filter(|key| !key.is_empty()).ok_or(Error::MissingClientField("api_key"))?;
+		let client_version = self
+			.client_version
+			.filter(|version| !version.is_empty())
+			.ok_or(Error::MissingClientField("client_version"))?;
+		Ok(InnerTubeClient {
+			api_key,
+			client_version,
+			client_platform: self.client_platform,
+			visitor_data: self.visitor_data,
+			hl: self.hl,
+			gl: self.gl
+		})
+	}
+}
@@ -12,110 +12,279 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashSet, io::BufRead, pin::Pin, sync::OnceLock, time::Duration};
+use std::{collections::HashSet, io::BufRead, pin::Pin, time::Duration};
 
-use futures_util::Stream;
-use reqwest::header::{self, HeaderMap, HeaderValue};
+use futures_util::{Stream, TryStreamExt};
 use simd_json::base::{ValueAsContainer, ValueAsScalar};
 use thiserror::Error;
-use tokio::time::sleep;
+use tokio::{
+	sync::watch,
+	time::{sleep, timeout}
+};
+use url::Url;
 
+mod author;
+mod builder;
+mod client;
+mod command;
 mod context;
+mod dedup;
+mod diff;
 mod error;
+mod event;
+mod ext;
+mod filter;
+mod innertube;
+#[cfg(feature = "proto")]
+pub mod proto;
+mod replay;
 mod signaler;
+mod stats;
+mod superchat;
 mod types;
 mod util;
 
 pub use self::{
-	context::{ChannelSearchOptions, ChatContext, LiveStreamStatus},
+	author::{Author, AuthorAvatar, AuthorBuilder, AvatarCache, Badge},
+	builder::{ChatItemBuilder, SuperchatInfo},
+	client::{ClientPool, YouTubeConfig},
+	command::BotCommand,
+	context::{get_live_video_id_from_handle, ChannelSearchOptions, ChatContext, LiveStreamStatus},
+	dedup::FuzzyDedup,
+	diff::{diff, ChatDiff},
 	error::Error,
+	event::{stream_events, ChatEvent},
+	ext::{
+		ChatStreamExt, CountByAuthor, DiscordError, DiscordFormat, HeartbeatOrMessage, InterleaveHeartbeats, MessageBatcher, Sample, StreamEvent,
+		StreamHealthMonitor, TakeUntilEnd, Throttle, ZipTimestamps
+	},
+	filter::{EventType, MessageFilter},
+	innertube::{ClientPlatform, InnerTubeClient, InnerTubeClientBuilder},
+	replay::{record_to_file, replay_from_file, ChatReplay, InMemoryRecorder, TokenBucket},
+	signaler::{ChannelStatus, SignalerTransport, TopicResolver},
+	stats::{get_live_chat_statistics, ChatMode, LiveChatStats},
+	superchat::{superchat_tier_from_amount, superchat_tier_from_amount_with_rates, SuperchatQueue, SuperchatTier},
 	types::{
-		get_live_chat::{Action, ChatItem, MessageRendererBase},
+		borrowed::{BorrowedChatMessage, BorrowedRun},
+		get_live_chat::{Action, ChatItem, GetLiveChatResponse, MessageRendererBase, MessageReply, MessageSource, ValidationError},
 		ImageContainer, LocalizedRun, LocalizedText, Thumbnail, UnlocalizedText
 	}
 };
-use self::{
-	signaler::SignalerChannelInner,
-	types::get_live_chat::{Continuation, GetLiveChatResponse}
-};
+#[cfg(feature = "serde")]
+pub use self::types::get_live_chat::chat_item_from_json;
+use self::{signaler::SignalerChannelInner, types::get_live_chat::{ActionContainer, Continuation}};
 
-const TANGO_LIVE_ENDPOINT: &str = "https://www.youtube.com/youtubei/v1/live_chat/get_live_chat";
-const TANGO_REPLAY_ENDPOINT: &str = "https://www.youtube.com/youtubei/v1/live_chat/get_live_chat_replay";
+const TANGO_LIVE_ENDPOINT: &str = "youtubei/v1/live_chat/get_live_chat";
+const TANGO_REPLAY_ENDPOINT: &str = "youtubei/v1/live_chat/get_live_chat_replay";
 
 pub(crate) fn get_http_client() -> &'static reqwest::Client {
-	static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
-	HTTP_CLIENT.get_or_init(|| {
-		let mut headers = HeaderMap::new();
-		// Set our Accept-Language to en-US so we can properly match substrings
-		headers.append(header::ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.5"));
-		headers.append(header::USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:123.0) Gecko/20100101 Firefox/123.0"));
-		// Referer is required by Signaler endpoints.
-		headers.append(header::REFERER, HeaderValue::from_static("https://www.youtube.com/"));
-		reqwest::Client::builder().default_headers(headers).build().unwrap()
-	})
+	self::client::get_client_pool().get()
 }
 
-struct ActionChunk<'r> {
+/// Resolves `path` against the configured base URL; see [`YouTubeConfig::with_base_url`].
+pub(crate) fn youtube_url(path: &str) -> Result<Url, Error> {
+	Ok(self::client::get_client_pool().config().base_url.join(path)?)
+}
+
+/// A page of chat actions, along with what's needed to fetch the next page.
+///
+/// A chunk can be turned back into a stream with [`stream_from_chunk`], e.g. to resume a previous session without
+/// re-fetching the first page.
+pub struct ActionChunk<'r> {
 	actions: Vec<Action>,
+	/// The video offset of each action in `actions` that was flattened out of an [`Action::ReplayChat`].
+	replay_offsets: Vec<Option<i64>>,
 	ctx: &'r ChatContext,
 	continuation_token: Option<String>,
+	tracking_params: Option<String>,
+	kind: ChunkKind,
+	page_index: u64,
 	pub(crate) signaler_topic: Option<String>
 }
 
+/// How the page after a chunk should be fetched.
+#[derive(Debug, Clone, Copy)]
+enum ChunkKind {
+	/// Fetch the next page when the Signaler notifies us of an update.
+	Invalidation { timeout: Duration },
+	/// Fetch the next page after waiting `timeout`.
+	Timed { timeout: Duration },
+	/// Fetch the next page immediately.
+	Replay
+}
+
 unsafe impl<'r> Send for ActionChunk<'r> {}
 
 impl<'r> ActionChunk<'r> {
-	pub fn new(response: GetLiveChatResponse, ctx: &'r ChatContext) -> Result<Self, Error> {
-		let continuation_contents = response.continuation_contents.ok_or(Error::EndOfContinuation)?;
-
-		let continuation_token = match &continuation_contents.live_chat_continuation.continuations[0] {
-			Continuation::Invalidation { continuation, .. } => continuation.to_owned(),
-			Continuation::Timed { continuation, .. } => continuation.to_owned(),
-			Continuation::Replay { continuation, .. } => continuation.to_owned(),
-			Continuation::PlayerSeek { .. } => return Err(Error::EndOfContinuation)
+	pub(crate) fn new(response: GetLiveChatResponse, ctx: &'r ChatContext) -> Result<Self, Error> {
+		let mut continuation_contents = response.continuation_contents.ok_or(Error::EndOfContinuation)?;
+
+		// When a live stream ends, YouTube stops sending a continuation to fetch the next page with (or only sends one
+		// for seeking the replay), so there's nothing left to stream.
+		let (continuation_token, kind) = match continuation_contents.live_chat_continuation.continuations.first() {
+			// Without a Signaler API key (which pages for some clients, like the TV client, may not carry), there's no way to
+			// be notified of updates, so just poll on the page's timeout.
+			Some(Continuation::Invalidation { continuation, timeout_ms, .. }) if ctx.tango_api_key.is_none() => {
+				let timeout = Duration::from_millis(*timeout_ms as _);
+				(continuation.to_owned(), ChunkKind::Timed { timeout })
+			}
+			Some(Continuation::Invalidation { continuation, timeout_ms, .. }) => {
+				let timeout = Duration::from_millis(*timeout_ms as _);
+				(continuation.to_owned(), ChunkKind::Invalidation { timeout })
+			}
+			Some(Continuation::Timed { continuation, timeout_ms }) => {
+				let timeout = Duration::from_millis(*timeout_ms as _);
+				(continuation.to_owned(), ChunkKind::Timed { timeout })
+			}
+			Some(Continuation::Replay { continuation, .. }) => (continuation.to_owned(), ChunkKind::Replay),
+			Some(Continuation::PlayerSeek { .. }) | None => return Err(Error::EndOfContinuation)
 		};
-		let signaler_topic = match &continuation_contents.live_chat_continuation.continuations[0] {
-			Continuation::Invalidation { invalidation_id, .. } => Some(invalidation_id.topic.to_owned()),
+		let signaler_topic = match continuation_contents.live_chat_continuation.continuations.first() {
+			Some(Continuation::Invalidation { invalidation_id, .. }) => Some(invalidation_id.topic.to_owned()),
 			_ => None
 		};
+		let tracking_params = continuation_contents.live_chat_continuation.tracking_params.take();
+		let containers = if ctx.live_status.updates_live() {
+			continuation_contents.live_chat_continuation.actions.unwrap_or_default()
+		} else {
+			continuation_contents.live_chat_continuation.actions.ok_or(Error::EndOfContinuation)?
+		};
+		let mut actions = Vec::with_capacity(containers.len());
+		let mut replay_offsets = Vec::with_capacity(containers.len());
+		for container in containers {
+			match container.action {
+				Action::ReplayChat {
+					actions: replay_actions,
+					video_offset_time_msec
+				} => {
+					actions.extend(replay_actions.into_iter().map(|f| f.action));
+					replay_offsets.resize(actions.len(), Some(video_offset_time_msec));
+				}
+				action => {
+					actions.push(action);
+					replay_offsets.push(None);
+				}
+			}
+		}
+		if let Some(max_actions) = client::installed_config().and_then(|config| config.max_actions_per_page) {
+			if actions.len() > max_actions {
+				let dropped = actions.len() - max_actions;
+				tracing::warn!(dropped, "page exceeded the maximum number of actions; dropping the oldest");
+				actions.drain(..dropped);
+				replay_offsets.drain(..dropped);
+			}
+		}
 		Ok(Self {
-			actions: if ctx.live_status.updates_live() {
-				continuation_contents
-					.live_chat_continuation
-					.actions
-					.unwrap_or_default()
-					.into_iter()
-					.map(|f| f.action)
-					.collect()
-			} else {
-				continuation_contents
-					.live_chat_continuation
-					.actions
-					.ok_or(Error::EndOfContinuation)?
-					.into_iter()
-					.flat_map(|f| match f.action {
-						Action::ReplayChat { actions, .. } => actions.into_iter().map(|f| f.action).collect(),
-						f => vec![f]
-					})
-					.collect()
-			},
+			actions,
+			replay_offsets,
 			ctx,
 			continuation_token: Some(continuation_token),
+			tracking_params,
+			kind,
+			page_index: 0,
 			signaler_topic
 		})
 	}
 
+	/// Fetches the first page of chat for `ctx`.
+	pub async fn fetch(ctx: &'r ChatContext) -> Result<Self, Error> {
+		Self::new(GetLiveChatResponse::fetch(ctx, &ctx.initial_continuation, None).await?, ctx)
+	}
+
 	pub fn iter(&self) -> std::slice::Iter<'_, Action> {
 		self.actions.iter()
 	}
 
+	/// Returns the context this chunk was fetched with.
+	pub fn context(&self) -> &'r ChatContext {
+		self.ctx
+	}
+
+	/// Returns the Signaler topic live updates for this chunk are published to. Only live streams have a topic.
+	pub fn signaler_topic(&self) -> Option<&str> {
+		self.signaler_topic.as_deref()
+	}
+
+	/// Returns the token used to fetch the page after this one, e.g. to checkpoint a replay session.
+	pub fn continuation_token(&self) -> Option<&str> {
+		self.continuation_token.as_deref()
+	}
+
+	/// Returns the zero-based index of this page, counting from the chunk a session started with.
+	///
+	/// For replays, this can be used alongside the video offset of the last action & the video's duration to report
+	/// approximate progress through the chat.
+	pub fn page_index(&self) -> u64 {
+		self.page_index
+	}
+
+	/// Returns the number of actions in this chunk.
+	pub fn len(&self) -> usize {
+		self.actions.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.actions.is_empty()
+	}
+
+	/// Returns the actions in this chunk that should be yielded by the stream, skipping any chat items already in
+	/// `seen_messages`.
+	fn unseen_actions(&self, mut seen_messages: Option<&mut HashSet<String>>) -> Vec<Action> {
+		let mut actions = Vec::with_capacity(self.actions.len());
+		for action in self.iter() {
+			match action {
+				Action::AddChatItem { item, .. } => {
+					if let Some(seen_messages) = seen_messages.as_mut() {
+						if !seen_messages.insert(item.id().to_owned()) {
+							continue;
+						}
+					}
+					actions.push(action.to_owned());
+				}
+				action => actions.push(action.to_owned())
+			}
+		}
+		actions
+	}
+
+	/// Returns the actions in this chunk with replay actions wrapped back up in an [`Action::ReplayChat`] carrying
+	/// their video offset, as yielded when [`StreamOptions::replay_offsets`] is enabled.
+	fn replay_chat_actions(&self) -> Vec<Action> {
+		let mut actions: Vec<Action> = Vec::with_capacity(self.actions.len());
+		for (action, offset) in self.actions.iter().zip(&self.replay_offsets) {
+			let Some(offset) = *offset else {
+				actions.push(action.to_owned());
+				continue;
+			};
+			let container = ActionContainer {
+				action: action.to_owned(),
+				click_tracking_params: None
+			};
+			match actions.last_mut() {
+				Some(Action::ReplayChat { actions, video_offset_time_msec }) if *video_offset_time_msec == offset => actions.push(container),
+				_ => actions.push(Action::ReplayChat {
+					actions: vec![container],
+					video_offset_time_msec: offset
+				})
+			}
+		}
+		actions
+	}
+
 	pub async fn cont(&self) -> Option<Result<Self, Error>> {
 		if let Some(continuation_token) = &self.continuation_token {
-			let page = match GetLiveChatResponse::fetch(self.ctx, continuation_token).await {
+			let page = match GetLiveChatResponse::fetch(self.ctx, continuation_token, self.tracking_params.as_deref()).await {
 				Err(e) => return Some(Err(e)),
 				Ok(page) => page
 			};
-			if page.continuation_contents.is_some() { Some(ActionChunk::new(page, self.ctx)) } else { None }
+			if page.continuation_contents.is_some() {
+				Some(ActionChunk::new(page, self.ctx).map(|chunk| ActionChunk {
+					page_index: self.page_index + 1,
+					..chunk
+				}))
+			} else {
+				None
+			}
 		} else {
 			None
 		}
@@ -131,82 +300,256 @@ impl<'r> IntoIterator for ActionChunk<'r> {
 	}
 }
 
-pub async fn stream(options: &ChatContext) -> Result<Pin<Box<dyn Stream<Item = Result<Action, Error>> + '_>>, Error> {
-	let initial_chat = GetLiveChatResponse::fetch(options, &options.initial_continuation).await?;
+impl<'a, 'r> IntoIterator for &'a ActionChunk<'r> {
+	type Item = &'a Action;
+	type IntoIter = std::slice::Iter<'a, Action>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.iter()
+	}
+}
+
+/// Options controlling the behavior of a chat stream created with [`stream_with_options`].
+#[derive(Debug, Default)]
+pub struct StreamOptions {
+	/// Receives updates on the state of the Signaler connection used to get live chat updates in real time. Only live
+	/// streams use the Signaler, so this does nothing for replays.
+	///
+	/// ```no_run
+	/// use brainrot::youtube::{self, ChannelStatus, StreamOptions};
+	/// use tokio::sync::watch;
+	///
+	/// # #[tokio::main]
+	/// # async fn main() -> anyhow::Result<()> {
+	/// let context = youtube::ChatContext::new_from_live("jfKfPfyJRdk").await?;
+	/// let (status_tx, status_rx) = watch::channel(ChannelStatus::Connecting);
+	/// let mut options = StreamOptions::default();
+	/// options.signaler_status = Some(status_tx);
+	/// let stream = youtube::stream_with_options(&context, options).await?;
+	/// println!("{:?}", *status_rx.borrow());
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub signaler_status: Option<watch::Sender<ChannelStatus>>,
+	/// How long to wait for a Signaler update before fetching the next page of a live stream's chat anyway. This keeps
+	/// the stream from stalling if an update is missed, which is more likely in streams with very little chat activity.
+	///
+	/// Defaults to the timeout YouTube sends alongside each page, which is what the web client uses.
+	pub poll_interval: PollInterval,
+	/// When set, a page that came back with no actions is followed up by fetching the next page after this interval,
+	/// instead of waiting for the next Signaler update or [`poll_interval`](Self::poll_interval). This helps catch up
+	/// quickly with live streams during quiet periods, where updates may be sparse.
+	pub on_empty_page_poll_interval: Option<Duration>,
+	/// How many times to try (re)connecting to the Signaler before giving up, with exponential backoff between
+	/// attempts. Once all attempts have failed, [`ChannelStatus::Failed`] is reported and the stream ends.
+	///
+	/// Defaults to 10.
+	pub max_reconnect_attempts: Option<u32>,
+	/// Caps the number of retries over the whole lifetime of the stream, across every reconnect: failed Signaler
+	/// connection attempts, Signaler sessions that fail, and pages of chat that fail to fetch. Once exceeded, the
+	/// stream yields [`Error::MaxRetriesExceeded`] and ends. Pages that fail with an error that isn't
+	/// [retryable](Error::is_retryable) aren't retried at all: the error is yielded and the stream ends.
+	///
+	/// Unlike [`max_reconnect_attempts`](Self::max_reconnect_attempts), which resets once a connection succeeds, this
+	/// stops streams that keep failing intermittently. Only applies to live streams. Defaults to
+	/// [`YouTubeConfig::max_retries`], or no limit if that isn't set either.
+	pub max_total_retries: Option<u32>,
+	/// How to receive updates from the Signaler. Defaults to [`SignalerTransport::Streaming`].
+	pub signaler_transport: SignalerTransport,
+	/// Yield the actions of a replay wrapped in [`Action::ReplayChat`], which carries the offset into the video each
+	/// action was sent at, instead of flattening them. See [`ChatStreamExt::zip_timestamps`].
+	pub replay_offsets: bool
+}
+
+impl StreamOptions {
+	const DEFAULT_MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+	/// Returns how long to wait before fetching the page after `chunk`, given the usual `interval`.
+	fn next_poll_interval(&self, chunk: &ActionChunk<'_>, interval: Duration) -> Duration {
+		match self.on_empty_page_poll_interval {
+			Some(empty_interval) if chunk.is_empty() => empty_interval,
+			_ => interval
+		}
+	}
+
+	fn retry_budget(&self) -> RetryBudget {
+		let max = self
+			.max_total_retries
+			.or_else(|| client::installed_config().and_then(|config| config.max_retries));
+		RetryBudget::new(max)
+	}
+
+	fn report_status(&self, status: ChannelStatus) {
+		if let Some(sender) = &self.signaler_status {
+			sender.send_replace(status);
+		}
+	}
+}
+
+/// Counts the retries made over the lifetime of a stream against [`StreamOptions::max_total_retries`].
+#[derive(Debug)]
+pub(crate) struct RetryBudget {
+	used: u32,
+	max: Option<u32>
+}
+
+impl RetryBudget {
+	fn new(max: Option<u32>) -> Self {
+		Self { used: 0, max }
+	}
+
+	/// Records a retry, returning `false` if the budget has already been used up.
+	pub(crate) fn take(&mut self) -> bool {
+		if self.max.is_some_and(|max| self.used >= max) {
+			return false;
+		}
+		self.used += 1;
+		true
+	}
+
+	pub(crate) fn exceeded(&self) -> Error {
+		Error::MaxRetriesExceeded(self.used)
+	}
+}
+
+/// How long a live stream waits for a Signaler update before fetching the next page anyway; see
+/// [`StreamOptions::poll_interval`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PollInterval {
+	/// Use the timeout YouTube sends alongside each page.
+	#[default]
+	Page,
+	Fixed(Duration),
+	/// Start from the page's timeout (clamped to `min..=max`), then divide the interval by `scale_factor` after each
+	/// page with actions and multiply it by `scale_factor` after each empty page. Busy chats are polled more often,
+	/// and quiet chats less, staying within `min` and `max`.
+	///
+	/// `scale_factor` should be greater than `1.0`; anything that isn't a positive, finite number keeps the interval
+	/// fixed at its initial value. If `min` is greater than `max`, the two are swapped.
+	Adaptive {
+		min: Duration,
+		max: Duration,
+		scale_factor: f32
+	}
+}
+
+impl PollInterval {
+	fn initial(&self, page_timeout: Duration) -> Duration {
+		match *self {
+			PollInterval::Page => page_timeout,
+			PollInterval::Fixed(interval) => interval,
+			PollInterval::Adaptive { min, max, .. } => page_timeout.clamp(min.min(max), min.max(max))
+		}
+	}
+
+	fn adjust(&self, current: Duration, chunk: &ActionChunk<'_>) -> Duration {
+		match *self {
+			PollInterval::Adaptive { min, max, scale_factor } if scale_factor > 0.0 && scale_factor.is_finite() => {
+				let (min, max) = (min.min(max), min.max(max));
+				let next = if chunk.is_empty() {
+					current.as_secs_f32() * scale_factor
+				} else {
+					current.as_secs_f32() / scale_factor
+				};
+				// Scaling can overflow a `Duration`, which is as good as reaching `max`.
+				Duration::try_from_secs_f32(next).unwrap_or(max).clamp(min, max)
+			}
+			_ => current
+		}
+	}
+}
+
+pub async fn stream(options: &ChatContext) -> Result<Pin<Box<dyn Stream<Item = Result<Action, Error>> + Send + '_>>, Error> {
+	stream_with_options(options, StreamOptions::default()).await
+}
+
+pub async fn stream_with_options(
+	options: &ChatContext,
+	stream_options: StreamOptions
+) -> Result<Pin<Box<dyn Stream<Item = Result<Action, Error>> + Send + '_>>, Error> {
+	Ok(stream_from_chunk(ActionChunk::fetch(options).await?, stream_options))
+}
+
+/// Creates a chat stream that continues on from `chunk`, without fetching the first page again. The actions in
+/// `chunk` itself are yielded first.
+pub fn stream_from_chunk(chunk: ActionChunk<'_>, stream_options: StreamOptions) -> Pin<Box<dyn Stream<Item = Result<Action, Error>> + Send + '_>> {
+	Box::pin(stream_from_chunk_enumerated(chunk, stream_options).map_ok(|(_, action)| action))
+}
 
+/// A stream of actions paired with the index of the page they came from.
+pub type EnumeratedStream<'r> = Pin<Box<dyn Stream<Item = Result<(u64, Action), Error>> + Send + 'r>>;
+
+/// Like [`stream_with_options`], but pairs each action with the zero-based index of the page it came from (see
+/// [`ActionChunk::page_index`]), which is useful for logging & debugging.
+pub async fn stream_enumerated(options: &ChatContext, stream_options: StreamOptions) -> Result<EnumeratedStream<'_>, Error> {
+	Ok(stream_from_chunk_enumerated(ActionChunk::fetch(options).await?, stream_options))
+}
+
+/// Like [`stream_from_chunk`], but pairs each action with the index of the page it came from.
+pub fn stream_from_chunk_enumerated(chunk: ActionChunk<'_>, stream_options: StreamOptions) -> EnumeratedStream<'_> {
 	let (mut yield_tx, yield_rx) = unsafe { async_stream::__private::yielder::pair() };
 
-	Ok(Box::pin(async_stream::__private::AsyncStream::new(yield_rx, async move {
+	Box::pin(async_stream::__private::AsyncStream::new(yield_rx, async move {
 		let mut seen_messages = HashSet::new();
+		let mut chunk = chunk;
 
-		match &initial_chat.continuation_contents.as_ref().unwrap().live_chat_continuation.continuations[0] {
-			Continuation::Invalidation { invalidation_id, .. } => {
-				let topic = invalidation_id.topic.to_owned();
+		match chunk.kind {
+			ChunkKind::Invalidation { timeout: page_timeout } => {
+				let topic = chunk.signaler_topic.clone().unwrap();
+				let mut poll_interval = stream_options.poll_interval.initial(page_timeout);
+				let max_reconnect_attempts = stream_options
+					.max_reconnect_attempts
+					.unwrap_or(StreamOptions::DEFAULT_MAX_RECONNECT_ATTEMPTS);
 
-				let mut chunk = ActionChunk::new(initial_chat, options).unwrap();
+				let mut retries = stream_options.retry_budget();
 
-				let mut channel = SignalerChannelInner::with_topic(topic, options.tango_api_key.as_ref().unwrap());
-				channel.choose_server().await.unwrap();
-				channel.init_session().await.unwrap();
+				let mut channel = SignalerChannelInner::with_transport(topic, chunk.ctx.tango_api_key.as_ref().unwrap(), stream_options.signaler_transport);
 
-				for action in chunk.iter() {
-					match action {
-						Action::AddChatItem { item, .. } => {
-							if !seen_messages.contains(item.id()) {
-								yield_tx.send(Ok(action.to_owned())).await;
-								seen_messages.insert(item.id().to_owned());
-							}
-						}
-						Action::ReplayChat { actions, .. } => {
-							for action in actions {
-								if let Action::AddChatItem { .. } = action.action {
-									yield_tx.send(Ok(action.action.to_owned())).await;
-								}
-							}
-						}
-						action => {
-							yield_tx.send(Ok(action.to_owned())).await;
-						}
-					}
+				for action in chunk.unseen_actions(Some(&mut seen_messages)) {
+					yield_tx.send(Ok((chunk.page_index, action))).await;
 				}
 
 				'i: loop {
 					match chunk.cont().await {
 						Some(Ok(c)) => chunk = c,
-						Some(Err(err)) => eprintln!("{err:?}"),
+						Some(Err(Error::EndOfContinuation)) => break 'i,
+						Some(Err(err)) if !err.is_retryable() => {
+							// Report first; the consumer may drop the stream as soon as it sees the error.
+							stream_options.report_status(ChannelStatus::Failed(err.duplicate()));
+							yield_tx.send(Err(err)).await;
+							break 'i;
+						}
+						Some(Err(_)) if !retries.take() => {
+							yield_tx.send(Err(retries.exceeded())).await;
+							stream_options.report_status(ChannelStatus::Failed(retries.exceeded()));
+							break 'i;
+						}
+						Some(Err(err)) => tracing::warn!(error = ?err, "failed to fetch next chat page"),
 						_ => break 'i
 					};
 
-					for action in chunk.iter() {
-						match action {
-							Action::AddChatItem { item, .. } => {
-								if !seen_messages.contains(item.id()) {
-									yield_tx.send(Ok(action.to_owned())).await;
-									seen_messages.insert(item.id().to_owned());
-								}
-							}
-							Action::ReplayChat { actions, .. } => {
-								for action in actions {
-									if let Action::AddChatItem { .. } = action.action {
-										yield_tx.send(Ok(action.action.to_owned())).await;
-									}
-								}
-							}
-							action => {
-								yield_tx.send(Ok(action.to_owned())).await;
-							}
-						}
+					for action in chunk.unseen_actions(Some(&mut seen_messages)) {
+						yield_tx.send(Ok((chunk.page_index, action))).await;
 					}
 
-					let mut req = {
-						channel.reset();
-						channel.choose_server().await.unwrap();
-						channel.init_session().await.unwrap();
-						channel.get_session_stream().await.unwrap()
+					let mut req = match channel.connect_with_backoff(max_reconnect_attempts, &mut retries).await {
+						Ok(req) => req,
+						Err(Error::MaxRetriesExceeded(n)) => {
+							yield_tx.send(Err(Error::MaxRetriesExceeded(n))).await;
+							stream_options.report_status(ChannelStatus::Failed(Error::MaxRetriesExceeded(n)));
+							break 'i;
+						}
+						Err(err) => {
+							stream_options.report_status(ChannelStatus::Failed(err));
+							break 'i;
+						}
 					};
+					stream_options.report_status(ChannelStatus::Connected);
+					// Sessions end normally every so often; only reconnects after a failure count as retries.
+					let mut session_failed = false;
 					loop {
-						match req.chunk().await {
-							Ok(Some(s)) => {
+						match timeout(stream_options.next_poll_interval(&chunk, poll_interval), req.chunk()).await {
+							Ok(Ok(Some(s))) => {
 								let mut ofs_res_line = s.lines().nth(1).unwrap().unwrap();
 								if let Ok(s) = unsafe { simd_json::from_str::<simd_json::OwnedValue>(ofs_res_line.as_mut()) } {
 									let a = s.as_array().unwrap();
@@ -214,104 +557,105 @@ pub async fn stream(options: &ChatContext) -> Result<Pin<Box<dyn Stream<Item = R
 										channel.aid = a[a.len() - 1].as_array().unwrap()[0].as_usize().unwrap();
 									}
 								}
-
-								match chunk.cont().await {
-									Some(Ok(c)) => chunk = c,
-									Some(Err(err)) => eprintln!("{err:?}"),
-									_ => break 'i
-								};
-								channel.topic = chunk.signaler_topic.clone().unwrap();
-
-								for action in chunk.iter() {
-									match action {
-										Action::AddChatItem { item, .. } => {
-											if !seen_messages.contains(item.id()) {
-												yield_tx.send(Ok(action.to_owned())).await;
-												seen_messages.insert(item.id().to_owned());
-											}
-										}
-										Action::ReplayChat { actions, .. } => {
-											for action in actions {
-												if let Action::AddChatItem { .. } = action.action {
-													yield_tx.send(Ok(action.action.to_owned())).await;
-												}
-											}
-										}
-										action => {
-											yield_tx.send(Ok(action.to_owned())).await;
-										}
-									}
-								}
 							}
-							Ok(None) => break,
-							Err(e) => {
-								eprintln!("{e:?}");
+							// No update arrived in time; fetch the next page anyway in case we missed one.
+							Err(_) => {}
+							// Long polls end after every batch of updates; poll again within the same session.
+							Ok(Ok(None)) if matches!(channel.transport, SignalerTransport::LongPoll { .. }) => match channel.get_session_stream().await {
+								Ok(res) => {
+									req = res;
+									continue;
+								}
+								Err(e) => {
+									tracing::warn!("failed to poll Signaler: {e}");
+									session_failed = true;
+									break;
+								}
+							},
+							Ok(Ok(None)) => break,
+							Ok(Err(e)) => {
+								tracing::warn!("Signaler session closed unexpectedly: {e}");
+								session_failed = true;
 								break;
 							}
 						}
-					}
 
-					seen_messages.clear();
-				}
-			}
-			Continuation::Replay { .. } => {
-				let mut chunk = ActionChunk::new(initial_chat, options).unwrap();
-				loop {
-					for action in chunk.iter() {
-						match action {
-							Action::AddChatItem { .. } => {
-								yield_tx.send(Ok(action.to_owned())).await;
-							}
-							Action::ReplayChat { actions, .. } => {
-								for action in actions {
-									if let Action::AddChatItem { .. } = action.action {
-										yield_tx.send(Ok(action.action.to_owned())).await;
-									}
-								}
+						match chunk.cont().await {
+							Some(Ok(c)) => chunk = c,
+							Some(Err(Error::EndOfContinuation)) => break 'i,
+							Some(Err(err)) if !err.is_retryable() => {
+								stream_options.report_status(ChannelStatus::Failed(err.duplicate()));
+								yield_tx.send(Err(err)).await;
+								break 'i;
 							}
-							action => {
-								yield_tx.send(Ok(action.to_owned())).await;
+							Some(Err(_)) if !retries.take() => {
+								yield_tx.send(Err(retries.exceeded())).await;
+								stream_options.report_status(ChannelStatus::Failed(retries.exceeded()));
+								break 'i;
 							}
+							Some(Err(err)) => tracing::warn!(error = ?err, "failed to fetch next chat page"),
+							_ => break 'i
+						};
+						channel.topic = chunk.signaler_topic.clone().unwrap();
+						poll_interval = stream_options.poll_interval.adjust(poll_interval, &chunk);
+
+						for action in chunk.unseen_actions(Some(&mut seen_messages)) {
+							yield_tx.send(Ok((chunk.page_index, action))).await;
 						}
 					}
-					match chunk.cont().await {
-						Some(Ok(e)) => chunk = e,
-						_ => break
+					if session_failed && !retries.take() {
+						yield_tx.send(Err(retries.exceeded())).await;
+						stream_options.report_status(ChannelStatus::Failed(retries.exceeded()));
+						break 'i;
 					}
+					stream_options.report_status(ChannelStatus::Reconnecting);
+
+					seen_messages.clear();
 				}
 			}
-			Continuation::Timed { timeout_ms, .. } => {
-				let timeout = Duration::from_millis(*timeout_ms as _);
-				let mut chunk = ActionChunk::new(initial_chat, options).unwrap();
-				loop {
-					for action in chunk.iter() {
-						match action {
-							Action::AddChatItem { item, .. } => {
-								if !seen_messages.contains(item.id()) {
-									yield_tx.send(Ok(action.to_owned())).await;
-									seen_messages.insert(item.id().to_owned());
-								}
+			ChunkKind::Replay => loop {
+				let actions = if stream_options.replay_offsets {
+					chunk.replay_chat_actions()
+				} else {
+					chunk.unseen_actions(None)
+				};
+				for action in actions {
+					yield_tx.send(Ok((chunk.page_index, action))).await;
+				}
+				match chunk.cont().await {
+					Some(Ok(e)) => chunk = e,
+					_ => break
+				}
+			},
+			ChunkKind::Timed { timeout } => {
+				let mut retries = stream_options.retry_budget();
+				't: loop {
+					for action in chunk.unseen_actions(Some(&mut seen_messages)) {
+						yield_tx.send(Ok((chunk.page_index, action))).await;
+					}
+					loop {
+						sleep(stream_options.next_poll_interval(&chunk, timeout)).await;
+						match chunk.cont().await {
+							Some(Ok(c)) => {
+								chunk = c;
+								break;
 							}
-							Action::ReplayChat { actions, .. } => {
-								for action in actions {
-									if let Action::AddChatItem { .. } = action.action {
-										yield_tx.send(Ok(action.action.to_owned())).await;
-									}
-								}
+							Some(Err(Error::EndOfContinuation)) | None => break 't,
+							Some(Err(err)) if !err.is_retryable() => {
+								stream_options.report_status(ChannelStatus::Failed(err.duplicate()));
+								yield_tx.send(Err(err)).await;
+								break 't;
 							}
-							action => {
-								yield_tx.send(Ok(action.to_owned())).await;
+							Some(Err(_)) if !retries.take() => {
+								yield_tx.send(Err(retries.exceeded())).await;
+								stream_options.report_status(ChannelStatus::Failed(retries.exceeded()));
+								break 't;
 							}
+							Some(Err(err)) => tracing::warn!(error = ?err, "failed to fetch next chat page")
 						}
 					}
-					sleep(timeout).await;
-					match chunk.cont().await {
-						Some(Ok(e)) => chunk = e,
-						_ => break
-					}
 				}
 			}
-			Continuation::PlayerSeek { .. } => panic!("player seek should not be first continuation")
 		}
-	})))
+	}))
 }
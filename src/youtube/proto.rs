@@ -0,0 +1,187 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protobuf types for forwarding chat between services, generated from `proto/chat.proto`. Requires the `proto`
+//! feature.
+//!
+//! Only the parts of a chat item that make sense over the wire are kept: converting a [`ChatItem`] into a
+//! [`ChatMessage`] and back yields a text message (or Super Chat, if it has a purchase amount) with the same ID,
+//! author, timestamp & runs. Colors, member badge images & the rest of YouTube's renderer fields are dropped.
+//!
+//! ```
+//! use brainrot::youtube::{proto, ChatItem};
+//! use prost::Message;
+//!
+//! let item = ChatItem::builder().text("hello world").build();
+//! let encoded = proto::ChatMessage::from(item).encode_to_vec();
+//! let decoded = ChatItem::from(proto::ChatMessage::decode(&encoded[..]).unwrap());
+//! assert_eq!(decoded.message().unwrap().to_chat_string(), "hello world");
+//! ```
+
+use chrono::DateTime;
+
+use self::{
+	badge::Kind,
+	message_run::{Emoji, Run}
+};
+use crate::youtube::{self, types, ChatItem, ChatItemBuilder, ImageContainer, LocalizedRun, LocalizedText, SuperchatInfo, SuperchatTier, Thumbnail};
+
+// Generated from `proto/chat.proto` by `build.rs`.
+#[allow(clippy::all, clippy::pedantic)]
+mod generated {
+	include!(concat!(env!("OUT_DIR"), "/brainrot.youtube.rs"));
+}
+
+pub use self::generated::*;
+
+impl From<ChatItem> for ChatMessage {
+	fn from(item: ChatItem) -> Self {
+		let purchase_amount = match &item {
+			ChatItem::Superchat { purchase_amount_text, .. } | ChatItem::PaidSticker { purchase_amount_text, .. } => {
+				Some(purchase_amount_text.simple_text.clone())
+			}
+			_ => None
+		};
+		Self {
+			id: item.id().to_owned(),
+			author: item.author().map(Author::from),
+			timestamp_usec: item.timestamp().map_or(0, |timestamp| timestamp.timestamp_micros()),
+			runs: item
+				.message()
+				.map_or_else(Vec::new, |message| message.runs().iter().map(MessageRun::from).collect()),
+			purchase_amount
+		}
+	}
+}
+
+impl From<ChatMessage> for ChatItem {
+	fn from(message: ChatMessage) -> Self {
+		let mut builder = ChatItemBuilder::new()
+			.id(message.id)
+			.message(LocalizedText::new(message.runs.into_iter().filter_map(|run| run.run.map(LocalizedRun::from)).collect()));
+		if let Some(author) = message.author {
+			builder = builder.author(author.into());
+		}
+		if let Some(timestamp) = DateTime::from_timestamp_micros(message.timestamp_usec) {
+			builder = builder.timestamp(timestamp);
+		}
+		if let Some(purchase_amount) = message.purchase_amount {
+			// Tiers aren't sent over the wire, so use the lowest tier's colors.
+			builder = builder.superchat(SuperchatInfo::new(purchase_amount, SuperchatTier::from_usd(0.0)));
+		}
+		builder.build()
+	}
+}
+
+impl From<youtube::Author> for Author {
+	fn from(author: youtube::Author) -> Self {
+		Self {
+			avatar_url: author.avatar_image().map(|avatar| avatar.0),
+			id: author.id,
+			name: author.name,
+			badges: author.badges.iter().map(Badge::from).collect()
+		}
+	}
+}
+
+impl From<Author> for youtube::Author {
+	fn from(author: Author) -> Self {
+		Self {
+			id: author.id,
+			name: author.name,
+			avatar: image(author.avatar_url, None),
+			badges: author.badges.into_iter().map(youtube::Badge::from).collect()
+		}
+	}
+}
+
+impl From<&youtube::Badge> for Badge {
+	fn from(badge: &youtube::Badge) -> Self {
+		let (kind, tooltip) = match badge {
+			youtube::Badge::Owner => (Kind::Owner, None),
+			youtube::Badge::Moderator => (Kind::Moderator, None),
+			youtube::Badge::Verified => (Kind::Verified, None),
+			youtube::Badge::Member { tooltip, .. } => (Kind::Member, Some(tooltip.clone())),
+			youtube::Badge::Other { tooltip } => (Kind::Unspecified, Some(tooltip.clone()))
+		};
+		Self { kind: kind as i32, tooltip }
+	}
+}
+
+impl From<Badge> for youtube::Badge {
+	fn from(badge: Badge) -> Self {
+		let kind = badge.kind();
+		let tooltip = badge.tooltip.unwrap_or_default();
+		match kind {
+			Kind::Owner => youtube::Badge::Owner,
+			Kind::Moderator => youtube::Badge::Moderator,
+			Kind::Verified => youtube::Badge::Verified,
+			Kind::Member => youtube::Badge::Member { tooltip, image: image(None, None) },
+			Kind::Unspecified => youtube::Badge::Other { tooltip }
+		}
+	}
+}
+
+impl From<&LocalizedRun> for MessageRun {
+	fn from(run: &LocalizedRun) -> Self {
+		let run = match run {
+			LocalizedRun::Text { text } => Run::Text(text.clone()),
+			LocalizedRun::Emoji { emoji, .. } => Run::Emoji(Emoji {
+				id: emoji.emoji_id.clone(),
+				label: emoji
+					.image
+					.accessibility
+					.as_ref()
+					.map(|accessibility| accessibility.accessibility_data.label.clone())
+					.unwrap_or_default(),
+				is_custom: emoji.is_custom_emoji.unwrap_or(false),
+				image_url: emoji
+					.image
+					.thumbnails
+					.iter()
+					.max_by_key(|thumbnail| thumbnail.width)
+					.map(|thumbnail| thumbnail.url.clone())
+			})
+		};
+		Self { run: Some(run) }
+	}
+}
+
+impl From<Run> for LocalizedRun {
+	fn from(run: Run) -> Self {
+		match run {
+			Run::Text(text) => LocalizedRun::Text { text },
+			Run::Emoji(emoji) => LocalizedRun::Emoji {
+				emoji: types::Emoji {
+					emoji_id: emoji.id,
+					shortcuts: None,
+					search_terms: None,
+					supports_skin_tone: None,
+					image: image(emoji.image_url, Some(emoji.label)),
+					is_custom_emoji: Some(emoji.is_custom)
+				},
+				variant_ids: None
+			}
+		}
+	}
+}
+
+fn image(url: Option<String>, label: Option<String>) -> ImageContainer {
+	ImageContainer {
+		thumbnails: url.into_iter().map(|url| Thumbnail { url, width: None, height: None }).collect(),
+		accessibility: label.map(|label| types::Accessibility {
+			accessibility_data: types::AccessibilityData { label }
+		})
+	}
+}
@@ -0,0 +1,342 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	collections::HashSet,
+	future::Future,
+	path::Path,
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration
+};
+
+use futures_util::{Stream, StreamExt};
+use tokio::{
+	fs::{File, OpenOptions},
+	io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+	time::{sleep, timeout_at, Instant, Sleep}
+};
+
+use super::{types::get_live_chat::GetLiveChatResponse, Action, ActionChunk, ChatContext, Error};
+
+/// Replays a recorded list of actions in (scaled) real time, for testing overlays & bots offline.
+///
+/// Replay actions are paced by their offset into the video; chat items are paced by the time they were originally
+/// sent. These are different clocks, so the first timed action picks which one paces the whole replay, and actions
+/// timed by the other clock are yielded immediately, as are actions with no time at all, like chat item removals.
+#[derive(Debug)]
+pub struct ChatReplay {
+	actions: std::vec::IntoIter<Action>,
+	speed: f32,
+	clock: Option<ReplayClock>,
+	last_time: Option<Duration>,
+	pending: Option<Action>,
+	sleep: Option<Pin<Box<Sleep>>>,
+	bucket: Option<TokenBucket>
+}
+
+impl ChatReplay {
+	/// Creates a new replay of `actions`. A `speed` of `1.0` replays in real time, `2.0` twice as fast, etc.
+	///
+	/// # Panics
+	/// Panics if `speed` is not a positive number.
+	pub fn new(actions: Vec<Action>, speed: f32) -> Self {
+		assert!(speed > 0.0 && speed.is_finite(), "replay speed must be a positive number");
+		Self {
+			actions: actions.into_iter(),
+			speed,
+			clock: None,
+			last_time: None,
+			pending: None,
+			sleep: None,
+			bucket: None
+		}
+	}
+
+	/// Limits how quickly bursts of actions are released, on top of the replay's own pacing. Actions that were sent at
+	/// the same time form a single burst and are released together, taking one token from `bucket`.
+	pub fn with_rate_limit(mut self, bucket: TokenBucket) -> Self {
+		self.bucket = Some(bucket);
+		self
+	}
+
+	fn action_time(action: &Action) -> Option<(ReplayClock, Duration)> {
+		match action {
+			Action::ReplayChat { .. } => action.time_since_stream_start().map(|t| (ReplayClock::VideoOffset, t)),
+			Action::AddChatItem { item, .. } => item
+				.timestamp()
+				.map(|t| (ReplayClock::SentAt, Duration::from_micros(t.timestamp_micros().max(0) as _))),
+			_ => None
+		}
+	}
+}
+
+/// Which timestamps a [`ChatReplay`] is paced by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReplayClock {
+	/// The offset into the video of a [`Action::ReplayChat`].
+	VideoOffset,
+	/// The wall-clock time a chat item was sent.
+	SentAt
+}
+
+impl Stream for ChatReplay {
+	type Item = Action;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = &mut *self;
+		loop {
+			if let Some(sleep) = this.sleep.as_mut() {
+				futures_util::ready!(sleep.as_mut().poll(cx));
+				this.sleep = None;
+				return Poll::Ready(this.pending.take());
+			}
+
+			let Some(action) = this.actions.next() else {
+				return Poll::Ready(None);
+			};
+			let Some((clock, time)) = Self::action_time(&action) else {
+				return Poll::Ready(Some(action));
+			};
+			if *this.clock.get_or_insert(clock) != clock {
+				return Poll::Ready(Some(action));
+			}
+			let last_time = this.last_time.replace(time);
+			let mut delay = match last_time {
+				Some(last_time) => time.saturating_sub(last_time).div_f32(this.speed),
+				None => Duration::ZERO
+			};
+			if let Some(bucket) = this.bucket.as_mut() {
+				if last_time != Some(time) {
+					delay = delay.max(bucket.reserve());
+				}
+			}
+			if delay.is_zero() {
+				return Poll::Ready(Some(action));
+			}
+
+			this.pending = Some(action);
+			this.sleep = Some(Box::pin(sleep(delay)));
+		}
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let remaining = self.actions.len() + usize::from(self.pending.is_some());
+		(remaining, Some(remaining))
+	}
+}
+
+/// A token bucket rate limiter, which allows bursts of up to `capacity` events and refills at `rate` tokens per second.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+	rate: f32,
+	capacity: f32,
+	tokens: f32,
+	last_refill: Instant
+}
+
+impl TokenBucket {
+	/// Creates a full bucket that refills at `rate` tokens per second, with a capacity of 1 token.
+	///
+	/// # Panics
+	/// Panics if `rate` is not a positive number.
+	pub fn new(rate: f32) -> Self {
+		assert!(rate > 0.0 && rate.is_finite(), "token bucket rate must be a positive number");
+		Self {
+			rate,
+			capacity: 1.0,
+			tokens: 1.0,
+			last_refill: Instant::now()
+		}
+	}
+
+	/// Sets the maximum number of tokens the bucket can hold, i.e. the largest burst allowed at once. The bucket is
+	/// refilled to its new capacity.
+	///
+	/// # Panics
+	/// Panics if `capacity` is less than 1.
+	pub fn with_capacity(mut self, capacity: f32) -> Self {
+		assert!(capacity >= 1.0, "token bucket capacity must be at least 1");
+		self.capacity = capacity;
+		self.tokens = capacity;
+		self
+	}
+
+	/// Takes a token, returning how long to wait before acting on it.
+	///
+	/// Tokens taken while the bucket is empty are borrowed against future refills, so consecutive calls on an empty
+	/// bucket return increasing delays.
+	pub fn reserve(&mut self) -> Duration {
+		let now = Instant::now();
+		self.tokens = (self.tokens + now.duration_since(self.last_refill).as_secs_f32() * self.rate).min(self.capacity);
+		self.last_refill = now;
+
+		self.tokens -= 1.0;
+		if self.tokens >= 0.0 {
+			Duration::ZERO
+		} else {
+			Duration::from_secs_f32(-self.tokens / self.rate)
+		}
+	}
+}
+
+/// Captures the items of a stream in memory, for asserting on what a stream yielded in tests.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use brainrot::youtube::{self, InMemoryRecorder};
+///
+/// # #[tokio::main]
+/// # async fn main() -> anyhow::Result<()> {
+/// let context = youtube::ChatContext::new_from_live("jfKfPfyJRdk").await?;
+/// let mut recorder = InMemoryRecorder::new(youtube::stream(&context).await?);
+/// let received = recorder.collect_for(Duration::from_secs(10)).await.len();
+/// assert_eq!(recorder.collected().len(), received);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct InMemoryRecorder<S: Stream> {
+	stream: S,
+	items: Vec<S::Item>,
+	done: bool
+}
+
+impl<S: Stream + Unpin> InMemoryRecorder<S> {
+	pub fn new(stream: S) -> Self {
+		Self {
+			stream,
+			items: Vec::new(),
+			done: false
+		}
+	}
+
+	/// Collects items from the stream for `duration`, or until the stream ends. Returns the items collected during
+	/// this call; everything collected so far remains available through [`collected`](Self::collected).
+	pub async fn collect_for(&mut self, duration: Duration) -> &[S::Item] {
+		let start = self.items.len();
+		let deadline = Instant::now() + duration;
+		while !self.done {
+			match timeout_at(deadline, self.stream.next()).await {
+				Ok(Some(item)) => self.items.push(item),
+				Ok(None) => self.done = true,
+				Err(_) => break
+			}
+		}
+		&self.items[start..]
+	}
+
+	/// Returns every item collected so far.
+	pub fn collected(&self) -> &[S::Item] {
+		&self.items
+	}
+
+	/// Returns whether the underlying stream has ended.
+	pub fn is_done(&self) -> bool {
+		self.done
+	}
+
+	pub fn into_collected(self) -> Vec<S::Item> {
+		self.items
+	}
+}
+
+/// Fetches every page of chat from `options`, appending each raw JSON response to `output` as its own line. The last
+/// line is the response that ended the continuation.
+///
+/// For live streams, this waits between pages as long as YouTube asks, and runs until the stream ends. The resulting
+/// file can be read back with [`replay_from_file`] to develop against real chat data without hitting YouTube
+/// repeatedly.
+pub async fn record_to_file(options: &ChatContext, output: impl AsRef<Path>) -> Result<(), Error> {
+	let mut file = OpenOptions::new().create(true).append(true).open(output).await?;
+	let mut continuation = options.initial_continuation.clone();
+	let mut tracking_params = None;
+	loop {
+		let mut raw = GetLiveChatResponse::fetch_raw(options, &continuation, tracking_params.as_deref()).await?;
+		file.write_all(&raw).await?;
+		file.write_all(b"\n").await?;
+
+		let response: GetLiveChatResponse = simd_json::from_slice(&mut raw)?;
+		let timeout = response.timeout();
+		let chunk = match ActionChunk::new(response, options) {
+			Ok(chunk) => chunk,
+			Err(Error::EndOfContinuation) => break,
+			Err(e) => return Err(e)
+		};
+		match chunk.continuation_token {
+			Some(token) => continuation = token,
+			None => break
+		}
+		tracking_params = chunk.tracking_params;
+
+		if let Some(timeout) = timeout {
+			sleep(timeout).await;
+		}
+	}
+	file.flush().await?;
+	Ok(())
+}
+
+/// Streams the actions from a file of responses recorded by [`record_to_file`], as if they were being fetched from
+/// YouTube. Pages are processed in order as fast as they're polled; wrap the stream in a [`ChatReplay`] for realistic
+/// timing.
+///
+/// `options` should be the context the file was recorded with, or at least have the same [`LiveStreamStatus`].
+///
+/// [`LiveStreamStatus`]: super::LiveStreamStatus
+pub fn replay_from_file<'r>(path: impl AsRef<Path> + 'r, options: &'r ChatContext) -> impl Stream<Item = Result<Action, Error>> + 'r {
+	let (mut yield_tx, yield_rx) = unsafe { async_stream::__private::yielder::pair() };
+
+	async_stream::__private::AsyncStream::new(yield_rx, async move {
+		let file = match File::open(path).await {
+			Ok(file) => file,
+			Err(e) => {
+				yield_tx.send(Err(e.into())).await;
+				return;
+			}
+		};
+		let mut lines = BufReader::new(file).lines();
+		let mut seen_messages = HashSet::new();
+		loop {
+			let mut line = match lines.next_line().await {
+				Ok(Some(line)) => line.into_bytes(),
+				Ok(None) => break,
+				Err(e) => {
+					yield_tx.send(Err(e.into())).await;
+					break;
+				}
+			};
+			let response: GetLiveChatResponse = match simd_json::from_slice(&mut line) {
+				Ok(response) => response,
+				Err(e) => {
+					yield_tx.send(Err(e.into())).await;
+					break;
+				}
+			};
+			let chunk = match ActionChunk::new(response, options) {
+				Ok(chunk) => chunk,
+				Err(Error::EndOfContinuation) => break,
+				Err(e) => {
+					yield_tx.send(Err(e)).await;
+					break;
+				}
+			};
+			let seen_messages = if options.live_status.updates_live() { Some(&mut seen_messages) } else { None };
+			for action in chunk.unseen_actions(seen_messages) {
+				yield_tx.send(Ok(action)).await;
+			}
+		}
+	})
+}
@@ -29,7 +29,7 @@
 //   But through the chaos, a solution may glare.  //
 // /////////////////////////////////////////////// //
 
-use std::{collections::HashMap, io::BufRead, iter};
+use std::{collections::HashMap, io::BufRead, iter, time::Duration};
 
 use rand::Rng;
 use reqwest::{header, Response};
@@ -37,13 +37,77 @@ use simd_json::{
 	base::{ValueAsContainer, ValueAsScalar},
 	OwnedValue
 };
+use tokio::time::sleep;
 use url::Url;
 
-use super::{util::SimdJsonResponseBody, Error};
+use super::{
+	util::{SimdJsonResponseBody, TracedSend},
+	ActionChunk, Error, RetryBudget
+};
 
 const GCM_SIGNALER_SRQE: &str = "https://signaler-pa.youtube.com/punctual/v1/chooseServer";
 const GCM_SIGNALER_PSUB: &str = "https://signaler-pa.youtube.com/punctual/multi-watch/channel";
 
+const RECONNECT_BACKOFF_INITIAL: Duration = Duration::from_secs(1);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+/// The state of the Signaler connection used to receive live chat updates in real time.
+#[derive(Debug)]
+pub enum ChannelStatus {
+	/// The channel has not connected yet.
+	Connecting,
+	/// The channel is connected and waiting for updates.
+	Connected,
+	/// The connection was closed and is being re-established.
+	Reconnecting,
+	/// The channel failed to connect. The stream will not receive any further updates.
+	Failed(Error)
+}
+
+/// How updates are received from the Signaler.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SignalerTransport {
+	/// Keep a single HTTP response open and read updates from it as they arrive, like the web client does.
+	#[default]
+	Streaming,
+	/// Poll for updates with one request per batch of updates. This is slower, but works behind proxies & firewalls
+	/// that buffer or cut off long-lived streaming responses.
+	///
+	/// Each poll is abandoned after `timeout` if no updates arrive.
+	LongPoll { timeout: Duration }
+}
+
+/// Maps Signaler topics back to the ID of the video they belong to, for applications following several streams at once.
+///
+/// A stream's topic can change from page to page, so each new topic should be registered as it's seen.
+#[derive(Debug, Default, Clone)]
+pub struct TopicResolver {
+	topics: HashMap<String, String>
+}
+
+impl TopicResolver {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Records that `topic` belongs to the video `video_id`.
+	pub fn register(&mut self, video_id: &str, topic: &str) {
+		self.topics.insert(topic.to_owned(), video_id.to_owned());
+	}
+
+	/// Registers the Signaler topic of `chunk`, if it has one, under the ID of the video it was fetched from.
+	pub fn register_chunk(&mut self, chunk: &ActionChunk<'_>) {
+		if let Some(topic) = chunk.signaler_topic() {
+			self.register(chunk.context().id(), topic);
+		}
+	}
+
+	/// Returns the ID of the video `topic` belongs to.
+	pub fn resolve(&self, topic: &str) -> Option<&str> {
+		self.topics.get(topic).map(String::as_str)
+	}
+}
+
 #[derive(Debug, Default)]
 pub struct SignalerChannelInner {
 	pub(crate) topic: String,
@@ -52,7 +116,8 @@ pub struct SignalerChannelInner {
 	sid: Option<String>,
 	rid: usize,
 	pub(crate) aid: usize,
-	session_n: usize
+	session_n: usize,
+	pub(crate) transport: SignalerTransport
 }
 
 impl SignalerChannelInner {
@@ -64,6 +129,13 @@ impl SignalerChannelInner {
 		}
 	}
 
+	pub fn with_transport(topic: impl ToString, tango_key: impl ToString, transport: SignalerTransport) -> Self {
+		Self {
+			transport,
+			..Self::with_topic(topic, tango_key)
+		}
+	}
+
 	pub fn reset(&mut self) {
 		self.gsessionid = None;
 		self.sid = None;
@@ -83,8 +155,9 @@ impl SignalerChannelInner {
 			.post(Url::parse_with_params(GCM_SIGNALER_SRQE, [("key", &self.tango_key)])?)
 			.header(header::CONTENT_TYPE, "application/json+protobuf")
 			.body(format!(r#"[[null,null,null,[7,5],null,[["youtube_live_chat_web"],[1],[[["{}"]]]]]]"#, self.topic))
-			.send()
+			.send_traced()
 			.await?
+			.error_for_status()?
 			.simd_json()
 			.await?;
 		let gsess = server_response.as_array().unwrap()[0].as_str().unwrap();
@@ -120,8 +193,9 @@ impl SignalerChannelInner {
 			// body, in a GET request. where the error actually refers to this POST request. because that makes sense.
 			.header("X-WebChannel-Content-Type", "application/json+protobuf")
 			.form(&ofs_parameters)
-			.send()
-			.await?;
+			.send_traced()
+			.await?
+			.error_for_status()?;
 
 		let mut ofs_res_line = ofs.bytes().await?.lines().nth(1).unwrap().unwrap();
 		let value: OwnedValue = unsafe { simd_json::from_str(&mut ofs_res_line) }?;
@@ -133,8 +207,37 @@ impl SignalerChannelInner {
 		Ok(())
 	}
 
+	/// Resets and re-establishes the session, returning the session stream.
+	pub async fn connect(&mut self) -> Result<Response, Error> {
+		self.reset();
+		self.choose_server().await?;
+		self.init_session().await?;
+		self.get_session_stream().await
+	}
+
+	/// Like [`connect`](Self::connect), but retries up to `max_attempts` times if the connection fails, waiting 1
+	/// second before the first retry and doubling the wait after each failed attempt, up to 60 seconds. Each retry is
+	/// also taken from `retries`; once it runs out, this fails with [`Error::MaxRetriesExceeded`].
+	pub async fn connect_with_backoff(&mut self, max_attempts: u32, retries: &mut RetryBudget) -> Result<Response, Error> {
+		let mut backoff = RECONNECT_BACKOFF_INITIAL;
+		let mut attempt = 1;
+		loop {
+			match self.connect().await {
+				Ok(res) => return Ok(res),
+				Err(err) if attempt >= max_attempts => return Err(err),
+				Err(_) if !retries.take() => return Err(retries.exceeded()),
+				Err(err) => {
+					tracing::warn!(attempt, max_attempts, ?backoff, "failed to connect to Signaler, retrying: {err}");
+					sleep(backoff).await;
+					backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
+					attempt += 1;
+				}
+			}
+		}
+	}
+
 	pub async fn get_session_stream(&self) -> Result<Response, Error> {
-		Ok(super::get_http_client()
+		let request = super::get_http_client()
 			.get(Url::parse_with_params(
 				GCM_SIGNALER_PSUB,
 				[
@@ -144,14 +247,20 @@ impl SignalerChannelInner {
 					("RID", "rpc"),
 					("SID", self.sid.as_ref().unwrap()),
 					("AID", &self.aid.to_string()),
-					("CI", "0"),
+					// CI=1 asks the server to end the response after each batch of updates instead of streaming them.
+					("CI", if matches!(self.transport, SignalerTransport::LongPoll { .. }) { "1" } else { "0" }),
 					("TYPE", "xmlhttp"),
 					("zx", &Self::gen_zx()),
 					("t", "1")
 				]
 			)?)
-			.header(header::CONNECTION, "keep-alive")
-			.send()
-			.await?)
+			.header(header::CONNECTION, "keep-alive");
+		Ok(match self.transport {
+			SignalerTransport::Streaming => request,
+			SignalerTransport::LongPoll { timeout } => request.timeout(timeout)
+		}
+		.send_traced()
+		.await?
+		.error_for_status()?)
 	}
 }
@@ -0,0 +1,97 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde::Deserialize;
+use simd_json::{prelude::*, OwnedValue};
+
+use super::{context::get_page_contents, util::extract_json_object, youtube_url, Error};
+
+/// The state of a video's chat, as shown on its watch page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatMode {
+	/// Chat is open and receiving messages live.
+	Live,
+	/// The stream has ended, and its chat is available as a replay.
+	Replay,
+	/// The video has no chat, or the uploader has turned it off.
+	Disabled
+}
+
+/// Statistics about a stream's chat, scraped from its watch page. See [`get_live_chat_statistics`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveChatStats {
+	/// The number of people currently watching, if the stream is live and YouTube shows a viewer count for it.
+	pub concurrent_viewers: Option<u64>,
+	pub chat_mode: ChatMode
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VideoViewCountRenderer {
+	/// The raw viewer count, e.g. `1234`.
+	original_view_count: Option<String>,
+	/// The formatted viewer count, e.g. `1,234 watching now`.
+	view_count: Option<OwnedValue>,
+	#[serde(default)]
+	is_live: bool
+}
+
+impl VideoViewCountRenderer {
+	fn concurrent_viewers(&self) -> Option<u64> {
+		// For anything that isn't live, this is the total view count instead.
+		if !self.is_live {
+			return None;
+		}
+		if let Some(count) = self.original_view_count.as_deref().and_then(|count| count.parse().ok()) {
+			return Some(count);
+		}
+		let view_count = self.view_count.as_ref()?;
+		let text = match view_count.get_str("simpleText") {
+			Some(text) => text.to_owned(),
+			None => view_count.get_array("runs")?.iter().filter_map(|run| run.get_str("text")).collect()
+		};
+		let digits: String = text
+			.split_whitespace()
+			.next()?
+			.chars()
+			.filter(|c| !matches!(c, ',' | '.' | '\u{a0}'))
+			.collect();
+		digits.parse().ok()
+	}
+}
+
+/// Fetches the current viewer count & chat mode of a video from its watch page, without going through the YouTube Data
+/// API.
+pub async fn get_live_chat_statistics(video_id: &str) -> Result<LiveChatStats, Error> {
+	let page = get_page_contents(youtube_url(&format!("watch?v={video_id}"))?.as_str()).await?;
+
+	let concurrent_viewers = extract_json_object(&page.body, "videoViewCountRenderer")
+		.and_then(|renderer| unsafe { simd_json::from_str::<VideoViewCountRenderer>(&mut renderer.to_owned()) }.ok())
+		.and_then(|renderer| renderer.concurrent_viewers());
+
+	// Videos without chat either have no conversation bar, or one with a message saying chat is disabled instead of the
+	// chat frame.
+	let chat_mode = match extract_json_object(&page.body, "conversationBar").and_then(|bar| extract_json_object(bar, "liveChatRenderer")) {
+		Some(renderer) => match unsafe { simd_json::from_str::<OwnedValue>(&mut renderer.to_owned()) }
+			.ok()
+			.and_then(|renderer| renderer.get_bool("isReplay"))
+		{
+			Some(true) => ChatMode::Replay,
+			_ => ChatMode::Live
+		},
+		None => ChatMode::Disabled
+	};
+
+	Ok(LiveChatStats { concurrent_viewers, chat_mode })
+}
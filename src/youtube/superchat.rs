@@ -0,0 +1,190 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+	cmp::{Ordering, Reverse},
+	collections::{BinaryHeap, HashMap}
+};
+
+use super::ChatItem;
+
+/// Approximate value of one unit of each currency in US dollars, used by [`superchat_tier_from_amount`]. These only
+/// need to be accurate enough to place amounts in the right tier; use [`superchat_tier_from_amount_with_rates`] with
+/// your own rates if you need more precision.
+const USD_RATES: &[(&str, f64)] = &[
+	("USD", 1.0),
+	("EUR", 1.08),
+	("GBP", 1.27),
+	("JPY", 0.0067),
+	("KRW", 0.00075),
+	("TWD", 0.031),
+	("HKD", 0.128),
+	("CAD", 0.74),
+	("AUD", 0.66),
+	("NZD", 0.61),
+	("INR", 0.012),
+	("PHP", 0.018),
+	("IDR", 0.000064),
+	("MYR", 0.21),
+	("SGD", 0.74),
+	("THB", 0.028),
+	("BRL", 0.20),
+	("MXN", 0.058),
+	("ARS", 0.0011),
+	("CHF", 1.13),
+	("SEK", 0.096),
+	("NOK", 0.094),
+	("DKK", 0.145),
+	("PLN", 0.25),
+	("RUB", 0.011)
+];
+
+/// USD thresholds at which a Super Chat reaches each tier above the lowest.
+const TIER_THRESHOLDS: [f64; 8] = [2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0];
+
+/// The tier of a Super Chat, which determines its color & how long it's pinned for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuperchatTier {
+	/// `0` for Super Chats under $2, up to `8` for those of $500 or more.
+	pub level: u8,
+	/// The RGB color YouTube displays Super Chats of this tier with.
+	pub color: (u8, u8, u8)
+}
+
+impl SuperchatTier {
+	pub(crate) fn from_usd(usd: f64) -> Self {
+		let level = TIER_THRESHOLDS.iter().take_while(|threshold| usd >= **threshold).count() as u8;
+		let color = match level {
+			0 => (30, 136, 229),
+			1 => (0, 229, 255),
+			2 => (29, 233, 182),
+			3 => (255, 202, 40),
+			4 => (245, 124, 0),
+			5 => (233, 30, 99),
+			_ => (230, 33, 23)
+		};
+		Self { level, color }
+	}
+}
+
+/// Determines the tier of a Super Chat of `amount_micros` millionths of `currency` (an ISO 4217 code like `USD`),
+/// converting it to US dollars with a built-in table of approximate exchange rates.
+///
+/// Returns `None` if `currency` isn't in the table.
+///
+/// ```
+/// use brainrot::youtube::superchat_tier_from_amount;
+///
+/// let tier = superchat_tier_from_amount(20_000_000, "USD").unwrap();
+/// assert_eq!(tier.level, 4);
+/// ```
+pub fn superchat_tier_from_amount(amount_micros: u64, currency: &str) -> Option<SuperchatTier> {
+	let rate = USD_RATES.iter().find(|(code, _)| code.eq_ignore_ascii_case(currency))?.1;
+	Some(SuperchatTier::from_usd(amount_micros as f64 / 1_000_000.0 * rate))
+}
+
+/// Like [`superchat_tier_from_amount`], but converts to US dollars with `rates`, a map of currency codes to the value
+/// of one unit of that currency in US dollars.
+pub fn superchat_tier_from_amount_with_rates(amount_micros: u64, currency: &str, rates: &HashMap<String, f64>) -> Option<SuperchatTier> {
+	let rate = rates.get(currency)?;
+	Some(SuperchatTier::from_usd(amount_micros as f64 / 1_000_000.0 * rate))
+}
+
+/// A queue of paid messages that yields the largest amount first, e.g. for ordering donation alerts when several Super
+/// Chats arrive at once. Items with equal amounts are yielded in the order they were pushed.
+///
+/// Amounts should be in a common unit (e.g. micros of the same currency, or converted to US cents) for the ordering to
+/// be meaningful.
+///
+/// ```
+/// use brainrot::youtube::{ChatItem, SuperchatQueue};
+///
+/// # fn main() -> Result<(), simd_json::Error> {
+/// # let item = |id: &str| -> Result<ChatItem, _> {
+/// # simd_json::from_slice(&mut format!(r#"{{ "liveChatViewerEngagementMessageRenderer": {{ "id": "{id}" }} }}"#).into_bytes())
+/// # };
+/// let mut queue = SuperchatQueue::new();
+/// queue.push(item("small")?, 2_000_000);
+/// queue.push(item("large")?, 50_000_000);
+/// assert_eq!(queue.pop().map(|(item, amount)| (item.id().to_owned(), amount)), Some(("large".to_owned(), 50_000_000)));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct SuperchatQueue {
+	heap: BinaryHeap<QueuedSuperchat>,
+	next_sequence: u64
+}
+
+impl SuperchatQueue {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn push(&mut self, item: ChatItem, amount: u64) {
+		self.heap.push(QueuedSuperchat {
+			amount,
+			sequence: Reverse(self.next_sequence),
+			item
+		});
+		self.next_sequence += 1;
+	}
+
+	/// Removes & returns the item with the largest amount.
+	pub fn pop(&mut self) -> Option<(ChatItem, u64)> {
+		self.heap.pop().map(|queued| (queued.item, queued.amount))
+	}
+
+	/// Returns the item with the largest amount without removing it.
+	pub fn peek(&self) -> Option<(&ChatItem, u64)> {
+		self.heap.peek().map(|queued| (&queued.item, queued.amount))
+	}
+
+	pub fn len(&self) -> usize {
+		self.heap.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.heap.is_empty()
+	}
+}
+
+/// An entry in a [`SuperchatQueue`]. `BinaryHeap` is a max-heap, so this orders by amount, then by reverse insertion
+/// order so earlier items win ties.
+#[derive(Debug)]
+struct QueuedSuperchat {
+	amount: u64,
+	sequence: Reverse<u64>,
+	item: ChatItem
+}
+
+impl PartialEq for QueuedSuperchat {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for QueuedSuperchat {}
+
+impl PartialOrd for QueuedSuperchat {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for QueuedSuperchat {
+	fn cmp(&self, other: &Self) -> Ordering {
+		(self.amount, self.sequence).cmp(&(other.amount, other.sequence))
+	}
+}
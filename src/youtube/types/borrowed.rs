@@ -0,0 +1,123 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chrono::{DateTime, Utc};
+use simd_json::{prelude::*, BorrowedValue};
+
+/// A run of a [`BorrowedChatMessage`]'s text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BorrowedRun<'v> {
+	Text(&'v str),
+	/// An emoji. `label` is its accessibility label: the name of custom emoji, or the emoji itself for standard ones.
+	Emoji {
+		id: &'v str,
+		label: Option<&'v str>,
+		is_custom: bool
+	}
+}
+
+/// A text message or Super Chat whose strings borrow from the JSON it was parsed from.
+///
+/// Deserializing into [`ChatItem`](super::get_live_chat::ChatItem) copies every string out of the response. Consumers
+/// that look at each message once and then discard it can use this instead to skip those allocations. Only the fields
+/// needed to display a message are read.
+///
+/// ```
+/// use brainrot::youtube::BorrowedChatMessage;
+///
+/// let mut json = br#"{"liveChatTextMessageRenderer":{
+/// "id": "abc", "authorExternalChannelId": "UCabc", "timestampUsec": "1700000000000000",
+/// "message": { "runs": [{ "text": "hello" }] }
+/// }}"#
+/// .to_vec();
+/// let item = simd_json::to_borrowed_value(&mut json)?;
+/// let message = BorrowedChatMessage::from_rendered_json(&item).unwrap();
+/// assert_eq!(message.id, "abc");
+/// assert_eq!(message.to_chat_string(), "hello");
+/// # Ok::<(), simd_json::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BorrowedChatMessage<'v> {
+	pub id: &'v str,
+	pub author_channel_id: &'v str,
+	pub author_name: Option<&'v str>,
+	pub sent_at: Option<DateTime<Utc>>,
+	pub runs: Vec<BorrowedRun<'v>>,
+	/// The formatted amount paid, e.g. `$5.00`, if this is a Super Chat.
+	pub purchase_amount: Option<&'v str>
+}
+
+impl<'v> BorrowedChatMessage<'v> {
+	/// Parses a chat item, i.e. the `item` of an `addChatItemAction`. Returns `None` if the item isn't a text message
+	/// or Super Chat, or is missing required fields.
+	pub fn from_rendered_json(value: &'v BorrowedValue<'_>) -> Option<Self> {
+		let renderer = value
+			.get("liveChatTextMessageRenderer")
+			.or_else(|| value.get("liveChatPaidMessageRenderer"))?;
+		let runs = match renderer.get("message").and_then(|message| message.get_array("runs")) {
+			Some(runs) => runs.iter().filter_map(BorrowedRun::from_json).collect(),
+			None => Vec::new()
+		};
+		Some(Self {
+			id: renderer.get_str("id")?,
+			author_channel_id: renderer.get_str("authorExternalChannelId")?,
+			author_name: renderer.get("authorName").and_then(|name| name.get_str("simpleText")),
+			sent_at: renderer
+				.get_str("timestampUsec")
+				.and_then(|timestamp| timestamp.parse().ok())
+				.and_then(DateTime::from_timestamp_micros),
+			runs,
+			purchase_amount: renderer.get("purchaseAmountText").and_then(|amount| amount.get_str("simpleText"))
+		})
+	}
+
+	/// Formats the message's text the same way as
+	/// [`LocalizedText::to_chat_string`](super::LocalizedText::to_chat_string).
+	pub fn to_chat_string(&self) -> String {
+		let mut out = String::new();
+		for run in &self.runs {
+			match run {
+				BorrowedRun::Text(text) => out.push_str(text),
+				BorrowedRun::Emoji {
+					label: Some(label), is_custom: true, ..
+				} => {
+					out.push(':');
+					out.push_str(label);
+					out.push(':');
+				}
+				BorrowedRun::Emoji { label: Some(label), .. } => out.push_str(label),
+				BorrowedRun::Emoji { label: None, .. } => {}
+			}
+		}
+		out
+	}
+}
+
+impl<'v> BorrowedRun<'v> {
+	fn from_json(run: &'v BorrowedValue<'_>) -> Option<Self> {
+		if let Some(text) = run.get_str("text") {
+			return Some(BorrowedRun::Text(text));
+		}
+		let emoji = run.get("emoji")?;
+		Some(BorrowedRun::Emoji {
+			id: emoji.get_str("emojiId")?,
+			label: emoji
+				.get("image")
+				.and_then(|image| image.get("accessibility"))
+				.and_then(|accessibility| accessibility.get("accessibilityData"))
+				.and_then(|data| data.get_str("label")),
+			is_custom: emoji.get_bool("isCustomEmoji").unwrap_or(false)
+		})
+	}
+}
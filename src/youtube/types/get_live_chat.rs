@@ -12,49 +12,130 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_aux::prelude::*;
-use simd_json::{base::ValueAsContainer, derived::ValueObjectAccessAsScalar};
-use url::Url;
+use simd_json::prelude::*;
 
+#[cfg(feature = "serde")]
+use super::serialize_datetime_utc_as_microseconds;
 use super::{deserialize_datetime_utc_from_microseconds, Accessibility, CommandMetadata, Icon, ImageContainer, LocalizedText, UnlocalizedText};
 use crate::youtube::{
 	get_http_client,
-	util::{SimdJsonRequestBody, SimdJsonResponseBody},
-	ChatContext, Error, TANGO_LIVE_ENDPOINT, TANGO_REPLAY_ENDPOINT
+	util::{SimdJsonRequestBody, TracedSend},
+	youtube_url, Author, Badge, ChatContext, Error, TANGO_LIVE_ENDPOINT, TANGO_REPLAY_ENDPOINT
 };
 
 #[derive(Serialize, Debug)]
 pub struct GetLiveChatRequestBody {
-	context: GetLiveChatRequestBodyContext,
+	context: GetLiveChatRequestContext,
 	continuation: String
 }
 
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+enum GetLiveChatRequestContext {
+	Client(GetLiveChatRequestBodyContext),
+	/// The `INNERTUBE_CONTEXT` object from the watch page, sent as-is.
+	InnerTube(simd_json::OwnedValue)
+}
+
 impl GetLiveChatRequestBody {
 	pub(crate) fn new(continuation: impl Into<String>, client_version: impl Into<String>, client_name: impl Into<String>) -> Self {
 		Self {
-			context: GetLiveChatRequestBodyContext {
+			context: GetLiveChatRequestContext::Client(GetLiveChatRequestBodyContext {
 				client: GetLiveChatRequestBodyContextClient {
 					client_version: client_version.into(),
-					client_name: client_name.into()
-				}
-			},
+					client_name: client_name.into(),
+					visitor_data: None,
+					hl: None,
+					gl: None
+				},
+				click_tracking: None
+			}),
 			continuation: continuation.into()
 		}
 	}
+
+	/// Creates a request body using the full `INNERTUBE_CONTEXT` from the watch page rather than only the client name
+	/// & version, so the request's context matches what the web client would send.
+	pub(crate) fn with_innertube_context(continuation: impl Into<String>, context: simd_json::OwnedValue) -> Self {
+		Self {
+			context: GetLiveChatRequestContext::InnerTube(context),
+			continuation: continuation.into()
+		}
+	}
+
+	/// Attaches the encrypted visitor data (`VISITOR_DATA`) from the watch page, identifying the session the request
+	/// belongs to.
+	pub(crate) fn with_visitor_data(mut self, data: &str) -> Self {
+		match &mut self.context {
+			GetLiveChatRequestContext::Client(context) => context.client.visitor_data = Some(data.to_owned()),
+			GetLiveChatRequestContext::InnerTube(context) => {
+				if let Some(client) = context.get_mut("client").and_then(|client| client.as_object_mut()) {
+					client.insert("visitorData".to_owned(), data.into());
+				}
+			}
+		}
+		self
+	}
+
+	/// Sets the interface language (`hl`) & content region (`gl`) the request is made with. Only applies to bodies
+	/// created with [`GetLiveChatRequestBody::new`].
+	pub(crate) fn with_locale(mut self, hl: Option<String>, gl: Option<String>) -> Self {
+		if let GetLiveChatRequestContext::Client(context) = &mut self.context {
+			context.client.hl = hl;
+			context.client.gl = gl;
+		}
+		self
+	}
+
+	/// Attaches the `trackingParams` of the previous page, like the web client does.
+	pub(crate) fn with_tracking_params(mut self, tracking_params: impl Into<String>) -> Self {
+		let tracking_params = tracking_params.into();
+		match &mut self.context {
+			GetLiveChatRequestContext::Client(context) => {
+				context.click_tracking = Some(GetLiveChatRequestBodyContextClickTracking {
+					click_tracking_params: tracking_params
+				});
+			}
+			GetLiveChatRequestContext::InnerTube(context) => {
+				if let Some(context) = context.as_object_mut() {
+					context.insert("clickTracking".to_owned(), simd_json::json!({ "clickTrackingParams": tracking_params }));
+				}
+			}
+		}
+		self
+	}
 }
 
 #[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct GetLiveChatRequestBodyContext {
-	client: GetLiveChatRequestBodyContextClient
+	client: GetLiveChatRequestBodyContextClient,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	click_tracking: Option<GetLiveChatRequestBodyContextClickTracking>
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetLiveChatRequestBodyContextClickTracking {
+	click_tracking_params: String
 }
 
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GetLiveChatRequestBodyContextClient {
 	client_version: String,
-	client_name: String
+	client_name: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	visitor_data: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	hl: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	gl: Option<String>
 }
 
 #[derive(Deserialize, Debug)]
@@ -65,19 +146,46 @@ pub struct GetLiveChatResponse {
 }
 
 impl GetLiveChatResponse {
-	pub async fn fetch(options: &ChatContext, continuation: impl AsRef<str>) -> Result<Self, Error> {
-		let body = GetLiveChatRequestBody::new(continuation.as_ref(), &options.client_version, "WEB");
-		Ok(get_http_client()
-			.post(Url::parse_with_params(
-				if options.live_status.updates_live() { TANGO_LIVE_ENDPOINT } else { TANGO_REPLAY_ENDPOINT },
-				[("key", options.api_key.as_str()), ("prettyPrint", "false")]
-			)?)
-			.simd_json(&body)?
-			.send()
-			.await?
-			.simd_json()
-			.await
-			.unwrap())
+	pub async fn fetch(options: &ChatContext, continuation: impl AsRef<str>, tracking_params: Option<&str>) -> Result<Self, Error> {
+		let mut raw = Self::fetch_raw(options, continuation, tracking_params).await?;
+		Ok(simd_json::from_slice(&mut raw)?)
+	}
+
+	/// Fetches a page of chat without deserializing it, returning the raw JSON response body.
+	#[tracing::instrument(skip_all, fields(video_id = %options.id, continuation = %continuation.as_ref().chars().take(20).collect::<String>()))]
+	pub async fn fetch_raw(options: &ChatContext, continuation: impl AsRef<str>, tracking_params: Option<&str>) -> Result<Vec<u8>, Error> {
+		let mut body = match &options.innertube_context {
+			Some(context) => GetLiveChatRequestBody::with_innertube_context(continuation.as_ref(), context.clone()),
+			None => GetLiveChatRequestBody::new(continuation.as_ref(), &options.client_version, options.client_platform.client_name())
+		};
+		if let Some(visitor_data) = &options.visitor_data {
+			body = body.with_visitor_data(visitor_data);
+		}
+		if let Some(tracking_params) = tracking_params {
+			body = body.with_tracking_params(tracking_params);
+		}
+		let endpoint = if options.live_status.updates_live() { TANGO_LIVE_ENDPOINT } else { TANGO_REPLAY_ENDPOINT };
+		Self::post_raw(endpoint, &options.api_key, options.visitor_data.as_deref(), &body).await
+	}
+
+	pub(crate) async fn post_raw(endpoint: &str, api_key: &str, visitor_data: Option<&str>, body: &GetLiveChatRequestBody) -> Result<Vec<u8>, Error> {
+		let mut url = youtube_url(endpoint)?;
+		url.query_pairs_mut().append_pair("key", api_key).append_pair("prettyPrint", "false");
+		let mut request = get_http_client().post(url);
+		// The web client identifies itself with its visitor data in a header too; sessions without it are more likely
+		// to start getting 403s after a while.
+		if let Some(visitor_data) = visitor_data {
+			request = request.header("X-Goog-Visitor-Id", visitor_data);
+		}
+		Ok(request.simd_json(body)?.send_traced().await?.error_for_status()?.bytes().await?.to_vec())
+	}
+
+	/// Returns the amount of time YouTube asks clients to wait before requesting the next page of a live stream.
+	pub fn timeout(&self) -> Option<Duration> {
+		match self.continuation_contents.as_ref()?.live_chat_continuation.continuations.first()? {
+			Continuation::Invalidation { timeout_ms, .. } | Continuation::Timed { timeout_ms, .. } => Some(Duration::from_millis(*timeout_ms as _)),
+			_ => None
+		}
 	}
 }
 
@@ -88,12 +196,18 @@ pub struct GetLiveChatResponseContinuationContents {
 }
 
 #[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct LiveChatContinuation {
+	/// How to fetch the next page. Empty once a live stream has ended.
+	#[serde(default)]
 	pub continuations: Vec<Continuation>,
-	pub actions: Option<Vec<ActionContainer>>
+	pub actions: Option<Vec<ActionContainer>>,
+	/// Opaque value YouTube uses for request attribution, sent back with the request for the next page.
+	pub tracking_params: Option<String>
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct ActionContainer {
 	#[serde(flatten)]
@@ -133,6 +247,7 @@ pub struct InvalidationId {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub enum Action {
 	#[serde(rename = "addChatItemAction")]
@@ -164,16 +279,107 @@ pub enum Action {
 		data: simd_json::OwnedValue
 	},
 	#[serde(rename = "liveChatReportModerationStateCommand")]
-	ReportModerationState(simd_json::OwnedValue)
+	ReportModerationState(simd_json::OwnedValue),
+	/// An action this crate doesn't recognize yet, or a known action whose payload failed to parse.
+	#[serde(untagged, deserialize_with = "deserialize_unknown_action")]
+	Unknown(simd_json::OwnedValue)
+}
+
+/// The tags of the [`Action`] variants above, to tell a malformed action apart from one we don't know about.
+const KNOWN_ACTIONS: &[&str] = &[
+	"addChatItemAction",
+	"replaceChatItemAction",
+	"removeChatItemAction",
+	"removeChatItemByAuthorAction",
+	"addLiveChatTickerItemAction",
+	"replayChatItemAction",
+	"addBannerToLiveChatCommand",
+	"liveChatReportModerationStateCommand"
+];
+
+/// The tags of the [`ChatItem`] variants, like [`KNOWN_ACTIONS`].
+const KNOWN_CHAT_ITEMS: &[&str] = &[
+	"liveChatTextMessageRenderer",
+	"liveChatPaidMessageRenderer",
+	"liveChatMembershipItemRenderer",
+	"liveChatPaidStickerRenderer",
+	"liveChatSponsorshipsGiftPurchaseAnnouncementRenderer",
+	"liveChatSponsorshipsGiftRedemptionAnnouncementRenderer",
+	"liveChatPlaceholderItemRenderer",
+	"liveChatViewerEngagementMessageRenderer",
+	"liveChatModeChangeMessageRenderer",
+	"liveChatAutoModMessageRenderer"
+];
+
+fn deserialize_unknown_action<'de, D: Deserializer<'de>>(deserializer: D) -> Result<simd_json::OwnedValue, D::Error> {
+	let value = simd_json::OwnedValue::deserialize(deserializer)?;
+	log_if_malformed(&value, KNOWN_ACTIONS);
+	Ok(value)
+}
+
+fn deserialize_unknown_chat_item<'de, D: Deserializer<'de>>(deserializer: D) -> Result<simd_json::OwnedValue, D::Error> {
+	let value = simd_json::OwnedValue::deserialize(deserializer)?;
+	log_if_malformed(&value, KNOWN_CHAT_ITEMS);
+	Ok(value)
+}
+
+/// Unknown variants would otherwise silently swallow known kinds that changed shape, so log those.
+fn log_if_malformed(value: &simd_json::OwnedValue, known: &[&str]) {
+	let Some(object) = value.as_object() else {
+		return;
+	};
+	if let Some(kind) = object.keys().find(|key| known.contains(&key.as_str())) {
+		tracing::debug!(kind = kind.as_str(), "failed to parse a known kind; treating it as unknown");
+	}
+}
+
+impl Action {
+	/// Returns `true` if this action adds a new item to chat.
+	pub fn is_add_chat_item(&self) -> bool {
+		matches!(self, Action::AddChatItem { .. })
+	}
+
+	/// Returns `true` if this is a batch of actions from a chat replay.
+	pub fn is_replay(&self) -> bool {
+		matches!(self, Action::ReplayChat { .. })
+	}
+
+	/// Returns `true` if this action deletes a single message.
+	pub fn is_delete(&self) -> bool {
+		matches!(self, Action::RemoveChatItem { .. })
+	}
+
+	/// Returns `true` if this action deletes all messages from an author, which YouTube does when they're timed out or
+	/// banned.
+	pub fn is_ban(&self) -> bool {
+		matches!(self, Action::RemoveChatItemByAuthor { .. })
+	}
+
+	/// Returns how far into the stream a replayed action was sent. Only [`Action::ReplayChat`] carries this offset.
+	pub fn time_since_stream_start(&self) -> Option<Duration> {
+		match self {
+			Action::ReplayChat { video_offset_time_msec, .. } => Some(Duration::from_millis((*video_offset_time_msec).max(0) as _)),
+			_ => None
+		}
+	}
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct AuthorBadge {
 	pub live_chat_author_badge_renderer: LiveChatAuthorBadgeRenderer
 }
 
+impl AuthorBadge {
+	/// Returns the type of the badge's icon, e.g. `OWNER` or `MODERATOR`. Membership badges use a custom image instead.
+	pub fn icon_type(&self) -> Option<&str> {
+		self.live_chat_author_badge_renderer.icon.as_ref().map(|icon| icon.icon_type.as_str())
+	}
+}
+
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct LiveChatAuthorBadgeRenderer {
 	pub custom_thumbnail: Option<ImageContainer>,
@@ -183,20 +389,63 @@ pub struct LiveChatAuthorBadgeRenderer {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct MessageRendererBase {
 	pub author_name: Option<UnlocalizedText>,
 	pub author_photo: ImageContainer,
+	// Skipped when empty so membership items, which declare their own `authorBadges`, don't serialize the key twice.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
 	pub author_badges: Option<Vec<AuthorBadge>>,
 	pub context_menu_endpoint: ContextMenuEndpoint,
 	pub id: String,
 	#[serde(deserialize_with = "deserialize_datetime_utc_from_microseconds")]
+	#[cfg_attr(feature = "serde", serde(serialize_with = "serialize_datetime_utc_as_microseconds"))]
 	pub timestamp_usec: DateTime<Utc>,
 	pub author_external_channel_id: String,
-	pub context_menu_accessibility: Accessibility
+	pub context_menu_accessibility: Accessibility,
+	/// The button linking a reply to the message it replies to; see [`ChatItem::reply_to`].
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub reply_button: Option<simd_json::OwnedValue>,
+	/// Extra items in the message's context menu, which can also link a reply to the message it replies to.
+	#[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+	pub context_menu_items: Option<simd_json::OwnedValue>
+}
+
+/// The message a chat message replies to; see [`ChatItem::reply_to`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MessageReply {
+	/// The ID of the message being replied to.
+	pub message_id: String,
+	/// The channel ID of the author of the message being replied to.
+	pub author_id: String
+}
+
+impl MessageReply {
+	/// Searches a renderer's reply data for the command referencing the replied-to message. The command is nested
+	/// differently in reply buttons & context menu items, so any object carrying both IDs is accepted.
+	fn find(value: &simd_json::OwnedValue) -> Option<Self> {
+		if let Some(array) = value.as_array() {
+			return array.iter().find_map(Self::find);
+		}
+		let object = value.as_object()?;
+		let message_id = ["messageId", "targetItemId"].into_iter().find_map(|key| object.get(key)?.as_str());
+		let author_id = ["authorExternalChannelId", "externalChannelId"]
+			.into_iter()
+			.find_map(|key| object.get(key)?.as_str());
+		match (message_id, author_id) {
+			(Some(message_id), Some(author_id)) => Some(Self {
+				message_id: message_id.to_owned(),
+				author_id: author_id.to_owned()
+			}),
+			_ => object.values().find_map(Self::find)
+		}
+	}
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct ContextMenuEndpoint {
 	pub command_metadata: CommandMetadata,
@@ -204,11 +453,13 @@ pub struct ContextMenuEndpoint {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LiveChatItemContextMenuEndpoint {
 	pub params: String
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub enum ChatItem {
 	#[serde(rename = "liveChatTextMessageRenderer")]
@@ -224,6 +475,8 @@ pub enum ChatItem {
 		#[serde(flatten)]
 		message_renderer_base: MessageRendererBase,
 		message: Option<LocalizedText>,
+		#[serde(rename = "headerSubtext")]
+		header_sub_text: Option<LocalizedText>,
 		purchase_amount_text: UnlocalizedText,
 		header_background_color: isize,
 		header_text_color: isize,
@@ -236,6 +489,10 @@ pub enum ChatItem {
 	MembershipItem {
 		#[serde(flatten)]
 		message_renderer_base: MessageRendererBase,
+		/// The message attached to a membership milestone, if any.
+		message: Option<LocalizedText>,
+		header_primary_text: Option<LocalizedText>,
+		#[serde(rename = "headerSubtext")]
 		header_sub_text: Option<LocalizedText>,
 		author_badges: Option<Vec<AuthorBadge>>
 	},
@@ -272,15 +529,45 @@ pub enum ChatItem {
 	Placeholder {
 		id: String,
 		#[serde(deserialize_with = "deserialize_datetime_utc_from_microseconds")]
+		#[cfg_attr(feature = "serde", serde(serialize_with = "serialize_datetime_utc_as_microseconds"))]
 		timestamp_usec: DateTime<Utc>
 	},
 	#[serde(rename = "liveChatViewerEngagementMessageRenderer")]
 	ViewerEngagement { id: String },
-	#[serde(untagged)]
+	/// Announces a change to the chat's mode, e.g. slow mode or members-only mode being turned on or off.
+	#[serde(rename = "liveChatModeChangeMessageRenderer")]
+	#[serde(rename_all = "camelCase")]
+	ModeChange {
+		id: String,
+		#[serde(deserialize_with = "deserialize_datetime_utc_from_microseconds")]
+		#[cfg_attr(feature = "serde", serde(serialize_with = "serialize_datetime_utc_as_microseconds"))]
+		timestamp_usec: DateTime<Utc>,
+		text: LocalizedText,
+		subtext: Option<LocalizedText>,
+		icon: Option<Icon>
+	},
+	/// A message held for review by YouTube's automated moderation, only visible to the channel's owner & moderators.
+	#[serde(rename = "liveChatAutoModMessageRenderer")]
+	#[serde(rename_all = "camelCase")]
+	AutoModMessage {
+		id: String,
+		/// The message that was held.
+		auto_moderated_item: Box<ChatItem>,
+		/// Explains why the message was held, e.g. `Held for review`.
+		header_text: Option<LocalizedText>
+	},
+	#[serde(untagged, deserialize_with = "deserialize_unknown_chat_item")]
 	Unknown(simd_json::OwnedValue)
 }
 
 impl ChatItem {
+	/// Returns a builder for constructing chat items by hand, e.g. as test fixtures. See [`ChatItemBuilder`].
+	///
+	/// [`ChatItemBuilder`]: crate::youtube::ChatItemBuilder
+	pub fn builder() -> crate::youtube::ChatItemBuilder {
+		crate::youtube::ChatItemBuilder::new()
+	}
+
 	pub fn id(&self) -> &str {
 		match self {
 			ChatItem::MembershipItem { message_renderer_base, .. } => &message_renderer_base.id,
@@ -291,7 +578,198 @@ impl ChatItem {
 			ChatItem::MembershipGiftRedemption { id, .. } => id,
 			ChatItem::Placeholder { id, .. } => id,
 			ChatItem::ViewerEngagement { id } => id,
+			ChatItem::ModeChange { id, .. } => id,
+			ChatItem::AutoModMessage { id, .. } => id,
 			ChatItem::Unknown(_) => ""
 		}
 	}
+
+	/// Returns the common renderer fields (author, timestamp, etc.) of chat items that have them.
+	pub fn message_renderer_base(&self) -> Option<&MessageRendererBase> {
+		match self {
+			ChatItem::TextMessage { message_renderer_base, .. } => Some(message_renderer_base),
+			ChatItem::Superchat { message_renderer_base, .. } => Some(message_renderer_base),
+			ChatItem::MembershipItem { message_renderer_base, .. } => Some(message_renderer_base),
+			ChatItem::PaidSticker { message_renderer_base, .. } => Some(message_renderer_base),
+			_ => None
+		}
+	}
+
+	/// Returns the message this item replies to, taken from its reply button or, failing that, its context menu.
+	pub fn reply_to(&self) -> Option<MessageReply> {
+		let base = self.message_renderer_base()?;
+		base.reply_button
+			.as_ref()
+			.and_then(MessageReply::find)
+			.or_else(|| base.context_menu_items.as_ref().and_then(MessageReply::find))
+	}
+
+	/// Returns the author of chat items that have one.
+	pub fn author(&self) -> Option<Author> {
+		let mut author = Author::from(self.message_renderer_base()?);
+		if let ChatItem::MembershipItem { .. } = self {
+			author.badges = self.author_badges().iter().map(Badge::from).collect();
+		}
+		Some(author)
+	}
+
+	fn author_badges(&self) -> &[AuthorBadge] {
+		match self {
+			// Membership items declare `authorBadges` themselves, so the flattened renderer base never receives it.
+			ChatItem::MembershipItem { author_badges, .. } => author_badges.as_deref().unwrap_or_default(),
+			item => item
+				.message_renderer_base()
+				.and_then(|base| base.author_badges.as_deref())
+				.unwrap_or_default()
+		}
+	}
+
+	/// Returns `true` if this item was sent by the owner of the channel streaming.
+	pub fn is_from_channel_owner(&self) -> bool {
+		self.author_badges().iter().any(|badge| badge.icon_type() == Some("OWNER"))
+	}
+
+	/// Returns `true` if this item was sent by one of the channel's moderators.
+	pub fn is_from_moderator(&self) -> bool {
+		self.author_badges().iter().any(|badge| badge.icon_type() == Some("MODERATOR"))
+	}
+
+	/// Returns the time this item was sent, if known.
+	pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+		match self {
+			ChatItem::Placeholder { timestamp_usec, .. } | ChatItem::ModeChange { timestamp_usec, .. } => Some(*timestamp_usec),
+			item => item.message_renderer_base().map(|base| base.timestamp_usec)
+		}
+	}
+
+	/// Returns `true` if this item is a paid message or sticker (Super Chat or Super Sticker).
+	pub fn is_super(&self) -> bool {
+		matches!(self, ChatItem::Superchat { .. } | ChatItem::PaidSticker { .. })
+	}
+
+	/// Returns the time this item was sent as milliseconds since the Unix epoch, if known.
+	pub fn timestamp_ms(&self) -> Option<i64> {
+		self.timestamp().map(|timestamp| timestamp.timestamp_millis())
+	}
+
+	/// Returns the message body of text messages & superchats.
+	#[must_use]
+	pub fn message(&self) -> Option<&LocalizedText> {
+		match self {
+			ChatItem::TextMessage { message, .. } => message.as_ref(),
+			ChatItem::Superchat { message, .. } => message.as_ref(),
+			_ => None
+		}
+	}
+
+	/// Returns the text of a superchat's header line, which is displayed separately from its message body.
+	pub fn superchat_header_text(&self) -> Option<String> {
+		match self {
+			ChatItem::Superchat { header_sub_text, .. } => header_sub_text.as_ref().map(LocalizedText::to_chat_string),
+			_ => None
+		}
+	}
+
+	/// Returns the most relevant text of this item, along with which field it came from.
+	///
+	/// This falls back to an item's header text when it has no message body, such as with membership items (where the
+	/// body is only present for milestones), so those items can still be displayed. Superchats without a message fall
+	/// back to their header subtext. Text messages have no fields to fall back on, as their renderer carries no text
+	/// besides `message`; neither do paid stickers, whose only text is the purchase amount.
+	#[must_use]
+	pub fn text_with_source(&self) -> Option<(MessageSource, &LocalizedText)> {
+		match self {
+			ChatItem::Superchat { message, header_sub_text, .. } => message
+				.as_ref()
+				.map(|text| (MessageSource::Message, text))
+				.or_else(|| header_sub_text.as_ref().map(|text| (MessageSource::HeaderSubtext, text))),
+			ChatItem::MembershipItem {
+				message,
+				header_primary_text,
+				header_sub_text,
+				..
+			} => message
+				.as_ref()
+				.map(|text| (MessageSource::Message, text))
+				.or_else(|| header_sub_text.as_ref().map(|text| (MessageSource::HeaderSubtext, text)))
+				.or_else(|| header_primary_text.as_ref().map(|text| (MessageSource::HeaderPrimaryText, text))),
+			item => item.message().map(|text| (MessageSource::Message, text))
+		}
+	}
+
+	/// Checks that this item's fields are consistent, returning the first problem found.
+	///
+	/// YouTube's responses aren't guaranteed to be well-formed, so this can be used to drop malformed items before they
+	/// reach e.g. an overlay or database. Only items sent by a viewer are checked; other kinds of items always pass.
+	pub fn validate(&self) -> Result<(), ValidationError> {
+		let Some(base) = self.message_renderer_base() else {
+			return Ok(());
+		};
+		if base.author_external_channel_id.is_empty() {
+			return Err(ValidationError::MissingAuthorId);
+		}
+		if base.timestamp_usec.timestamp_micros() <= 0 {
+			return Err(ValidationError::InvalidTimestamp(base.timestamp_usec));
+		}
+		match self {
+			ChatItem::TextMessage { message, .. } if message.as_ref().map_or(true, LocalizedText::is_empty) => Err(ValidationError::EmptyMessage),
+			ChatItem::Superchat { purchase_amount_text, .. } | ChatItem::PaidSticker { purchase_amount_text, .. }
+				if purchase_amount_text.simple_text.is_empty() =>
+			{
+				Err(ValidationError::MissingPurchaseAmount)
+			}
+			_ => Ok(())
+		}
+	}
+}
+
+/// A problem with a [`ChatItem`] found by [`ChatItem::validate`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ValidationError {
+	#[error("text message has no content")]
+	EmptyMessage,
+	#[error("chat item has no author channel ID")]
+	MissingAuthorId,
+	#[error("chat item has an invalid timestamp: {0}")]
+	InvalidTimestamp(DateTime<Utc>),
+	/// A Super Chat or Super Sticker has no purchase amount.
+	#[error("paid item has no purchase amount")]
+	MissingPurchaseAmount
+}
+
+/// Deserializes a single chat item, e.g. one saved from a recording made with
+/// [`record_to_file`](crate::youtube::record_to_file) or serialized with this crate's `serde` feature.
+///
+/// ```
+/// let json = r#"{ "liveChatViewerEngagementMessageRenderer": { "id": "abc" } }"#;
+/// let item = brainrot::youtube::chat_item_from_json(json)?;
+/// assert_eq!(item.id(), "abc");
+/// # Ok::<(), brainrot::youtube::Error>(())
+/// ```
+#[cfg(feature = "serde")]
+pub fn chat_item_from_json(json: &str) -> Result<ChatItem, Error> {
+	let mut json = json.as_bytes().to_vec();
+	Ok(simd_json::from_slice(&mut json)?)
+}
+
+/// Deserializes a chat item from JSON; see [`chat_item_from_json`].
+#[cfg(feature = "serde")]
+impl TryFrom<&str> for ChatItem {
+	type Error = Error;
+
+	fn try_from(json: &str) -> Result<Self, Self::Error> {
+		chat_item_from_json(json)
+	}
+}
+
+/// Which field of a chat item its text was taken from; see [`ChatItem::text_with_source`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageSource {
+	/// The item's message body.
+	Message,
+	/// The secondary header line, e.g. "Welcome to <membership level>!" for new members, or a superchat's header.
+	HeaderSubtext,
+	/// The primary header line, e.g. "Member for 6 months" for membership milestones.
+	HeaderPrimaryText
 }
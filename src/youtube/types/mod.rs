@@ -12,20 +12,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
+
 use serde::{de::Error, Deserialize, Deserializer};
 use serde_aux::field_attributes::deserialize_number_from_string;
 use simd_json::OwnedValue;
 
+pub mod borrowed;
+// These mirror YouTube's response schema, so not every deserialized field is read.
+#[allow(dead_code)]
 pub mod get_live_chat;
+#[allow(dead_code)]
 pub mod streams_page;
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct CommandMetadata {
 	pub web_command_metadata: OwnedValue
 }
 
 #[derive(Deserialize, Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct UnlocalizedText {
 	pub simple_text: String,
@@ -33,7 +41,9 @@ pub struct UnlocalizedText {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(untagged)]
+#[non_exhaustive]
 pub enum LocalizedRun {
 	Text {
 		text: String
@@ -58,31 +68,159 @@ impl LocalizedRun {
 			}
 		}
 	}
+
+	/// Returns the length of this run in characters, for laying out or truncating text. Emoji count as a single
+	/// character, regardless of how their label is rendered.
+	pub fn character_count(&self) -> usize {
+		match self {
+			Self::Text { text } => text.chars().count(),
+			Self::Emoji { .. } => 1
+		}
+	}
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct LocalizedText {
-	pub runs: Vec<LocalizedRun>
+	runs: Vec<LocalizedRun>
+}
+
+impl LocalizedText {
+	pub fn new(runs: Vec<LocalizedRun>) -> Self {
+		Self { runs }
+	}
+
+	pub fn runs(&self) -> &[LocalizedRun] {
+		&self.runs
+	}
+
+	pub fn into_runs(self) -> Vec<LocalizedRun> {
+		self.runs
+	}
+
+	pub fn to_chat_string(&self) -> String {
+		self.runs.iter().map(LocalizedRun::to_chat_string).collect()
+	}
+
+	pub fn run_count(&self) -> usize {
+		self.runs.len()
+	}
+
+	/// Returns the number of emoji in this text, counting repeated emoji each time they appear.
+	pub fn total_emoji_count(&self) -> usize {
+		self.runs.iter().filter(|run| matches!(run, LocalizedRun::Emoji { .. })).count()
+	}
+
+	/// Returns the number of distinct emoji in this text, by emoji ID.
+	pub fn unique_emoji_count(&self) -> usize {
+		self.runs
+			.iter()
+			.filter_map(|run| match run {
+				LocalizedRun::Emoji { emoji, .. } => Some(emoji.emoji_id.as_str()),
+				_ => None
+			})
+			.collect::<HashSet<_>>()
+			.len()
+	}
+
+	/// Returns `true` if this text has no runs, or only contains empty text runs.
+	pub fn is_empty(&self) -> bool {
+		self.runs.iter().all(|run| matches!(run, LocalizedRun::Text { text } if text.is_empty()))
+	}
+
+	/// Returns a copy of this text with its text runs cleaned up for display: HTML entities are decoded, control
+	/// characters & zero-width spaces are removed, and repeated zero-width joiners are collapsed into one.
+	pub fn sanitize(&self) -> LocalizedText {
+		LocalizedText {
+			runs: self
+				.runs
+				.iter()
+				.map(|run| match run {
+					LocalizedRun::Text { text } => LocalizedRun::Text { text: sanitize_text(text) },
+					run => run.clone()
+				})
+				.collect()
+		}
+	}
+}
+
+fn sanitize_text(text: &str) -> String {
+	let decoded = decode_html_entities(text);
+	let mut sanitized = String::with_capacity(decoded.len());
+	for c in decoded.chars() {
+		match c {
+			// zero-width space, word joiner, BOM
+			'\u{200b}' | '\u{2060}' | '\u{feff}' => {}
+			// zero-width non-joiner & joiner
+			'\u{200c}' | '\u{200d}' => {
+				if !sanitized.ends_with(c) {
+					sanitized.push(c);
+				}
+			}
+			c if c.is_control() => {}
+			c => sanitized.push(c)
+		}
+	}
+	sanitized
+}
+
+fn decode_html_entities(text: &str) -> String {
+	let mut decoded = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let entity = rest.find(';').map(|end| &rest[1..end]);
+		let c = entity.and_then(|entity| match entity {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			"nbsp" => Some('\u{a0}'),
+			_ => match entity.strip_prefix('#') {
+				Some(hex) if hex.starts_with(['x', 'X']) => u32::from_str_radix(&hex[1..], 16).ok().and_then(char::from_u32),
+				Some(dec) => dec.parse().ok().and_then(char::from_u32),
+				None => None
+			}
+		});
+		match (c, entity) {
+			(Some(c), Some(entity)) => {
+				decoded.push(c);
+				rest = &rest[entity.len() + 2..];
+			}
+			_ => {
+				decoded.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+	decoded.push_str(rest);
+	decoded
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ImageContainer {
 	pub thumbnails: Vec<Thumbnail>,
 	pub accessibility: Option<Accessibility>
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Accessibility {
 	pub accessibility_data: AccessibilityData
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AccessibilityData {
 	pub label: String
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Thumbnail {
 	pub url: String,
 	pub width: Option<usize>,
@@ -90,6 +228,7 @@ pub struct Thumbnail {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Emoji {
 	pub emoji_id: String,
@@ -101,11 +240,21 @@ pub struct Emoji {
 }
 
 #[derive(Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[serde(rename_all = "camelCase")]
 pub struct Icon {
 	pub icon_type: String
 }
 
+#[cfg(feature = "serde")]
+pub fn serialize_datetime_utc_as_microseconds<S>(datetime: &chrono::DateTime<chrono::Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+	S: serde::Serializer
+{
+	// YouTube sends timestamps as strings, so do the same to keep the serialized form identical to the original.
+	serializer.collect_str(&datetime.timestamp_micros())
+}
+
 pub fn deserialize_datetime_utc_from_microseconds<'de, D>(deserializer: D) -> Result<chrono::DateTime<chrono::Utc>, D::Error>
 where
 	D: Deserializer<'de>
@@ -119,3 +268,35 @@ where
 
 	DateTime::from_timestamp(seconds, nanos).ok_or_else(|| D::Error::custom("Couldn't parse the timestamp"))
 }
+
+#[cfg(test)]
+mod tests {
+	use super::decode_html_entities;
+
+	#[test]
+	fn decodes_named_entities() {
+		assert_eq!(decode_html_entities("&lt;b&gt; &amp; &quot;hi&quot; &apos;x&apos;&nbsp;"), "<b> & \"hi\" 'x'\u{a0}");
+	}
+
+	#[test]
+	fn decodes_numeric_references() {
+		assert_eq!(decode_html_entities("&#65;&#x42;&#X43; &#128512;"), "ABC \u{1f600}");
+	}
+
+	#[test]
+	fn keeps_malformed_entities() {
+		// No terminating `;`, so nothing after the `&` is an entity.
+		assert_eq!(decode_html_entities("fish & chips"), "fish & chips");
+		assert_eq!(decode_html_entities("&amp"), "&amp");
+		assert_eq!(decode_html_entities("&#xZZ;"), "&#xZZ;");
+		// Surrogates aren't valid `char`s.
+		assert_eq!(decode_html_entities("&#xD800;"), "&#xD800;");
+		assert_eq!(decode_html_entities("&bogus; &amp;"), "&bogus; &");
+	}
+
+	#[test]
+	fn decodes_entity_at_end_of_string() {
+		assert_eq!(decode_html_entities("a &gt;"), "a >");
+		assert_eq!(decode_html_entities("&"), "&");
+	}
+}
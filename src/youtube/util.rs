@@ -41,3 +41,57 @@ impl SimdJsonRequestBody for RequestBuilder {
 		Ok(self.body(simd_json::to_vec(json)?))
 	}
 }
+
+/// Sends requests with `debug`-level logs of each request's URL & the status of its response.
+pub trait TracedSend {
+	fn send_traced(self) -> impl Future<Output = Result<Response, reqwest::Error>>;
+}
+
+impl TracedSend for RequestBuilder {
+	async fn send_traced(self) -> Result<Response, reqwest::Error> {
+		let (client, request) = self.build_split();
+		let request = request?;
+		tracing::debug!(url = %request.url(), method = %request.method(), "sending request");
+		let response = client.execute(request).await?;
+		tracing::debug!(url = %response.url(), status = %response.status(), "received response");
+		Ok(response)
+	}
+}
+
+/// Extracts the JSON object assigned to `key` in a page's inline scripts, e.g. `"INNERTUBE_CONTEXT":{...}`.
+///
+/// Unlike a regex, this matches nested braces, skipping over any braces that appear inside string literals.
+pub(crate) fn extract_json_object<'s>(source: &'s str, key: &str) -> Option<&'s str> {
+	let key_start = source.find(&format!("\"{key}\""))?;
+	let after_key = source[key_start + key.len() + 2..].trim_start().strip_prefix(':')?.trim_start();
+	if !after_key.starts_with('{') {
+		return None;
+	}
+
+	let mut depth = 0usize;
+	let mut in_string = false;
+	let mut escaped = false;
+	for (i, c) in after_key.char_indices() {
+		if in_string {
+			match c {
+				_ if escaped => escaped = false,
+				'\\' => escaped = true,
+				'"' => in_string = false,
+				_ => {}
+			}
+			continue;
+		}
+		match c {
+			'"' => in_string = true,
+			'{' => depth += 1,
+			'}' => {
+				depth -= 1;
+				if depth == 0 {
+					return Some(&after_key[..=i]);
+				}
+			}
+			_ => {}
+		}
+	}
+	None
+}
@@ -0,0 +1,308 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A minimal stand-in for YouTube's watch page & InnerTube chat endpoints, serving preset chat messages so the full
+//! `ChatContext::new_from_live` → `stream` path can be tested without network access.
+
+// Each test binary only uses the parts of the server it needs.
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use brainrot::youtube::{ClientPool, YouTubeConfig};
+use simd_json::{json, OwnedValue};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream}
+};
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MockStreamKind {
+	Live,
+	Replay
+}
+
+struct MockRoute {
+	kind: MockStreamKind,
+	messages: Vec<String>,
+	watch_page: String,
+	/// Requests whose path starts with the first string are answered with the second as the status, and no body.
+	statuses: Vec<(String, String)>,
+	/// Chat requests for pages before this one are served normally, even if they match one of `statuses`.
+	statuses_from_page: usize,
+	/// Serve the TV client's page & `next` response, and only answer chat requests sent as the TV client.
+	tv: bool
+}
+
+/// An HTTP server on localhost that serves a single stream with ID [`MockYouTubeServer::VIDEO_ID`].
+///
+/// Each message is served on its own page of chat, followed by a final page without a continuation, which ends the
+/// stream.
+pub struct MockYouTubeServer {
+	url: Url
+}
+
+impl MockYouTubeServer {
+	pub const VIDEO_ID: &'static str = "mockstream1";
+
+	/// Starts a server for a live stream whose chat receives `messages`, in order.
+	pub async fn with_live_stream(messages: Vec<&str>) -> Self {
+		Self::start(MockStreamKind::Live, messages).await
+	}
+
+	/// Starts a server for an ended stream whose chat replay contains `messages`, in order.
+	pub async fn with_replay(messages: Vec<&str>) -> Self {
+		Self::start(MockStreamKind::Replay, messages).await
+	}
+
+	/// Starts a server for a live stream with no chat messages, which serves `html` as its watch page instead of the
+	/// minimal generated one, e.g. to test scraping a saved copy of a real page.
+	pub async fn with_watch_page(html: impl Into<String>) -> Self {
+		Self::serve(MockStreamKind::Live, Vec::new(), html.into()).await
+	}
+
+	/// Starts a server for a live stream with no chat messages, which answers requests to paths starting with each
+	/// prefix with the paired status line instead, e.g. `("/youtubei/v1/live_chat/", "429 Too Many Requests")`.
+	pub async fn with_statuses(statuses: Vec<(&str, &str)>) -> Self {
+		let statuses = statuses
+			.into_iter()
+			.map(|(prefix, status)| (prefix.to_owned(), status.to_owned()))
+			.collect();
+		Self::serve_route(MockRoute {
+			kind: MockStreamKind::Live,
+			messages: Vec::new(),
+			watch_page: watch_page(MockStreamKind::Live),
+			statuses,
+			statuses_from_page: 0,
+			tv: false
+		})
+		.await
+	}
+
+	/// Like [`with_statuses`](Self::with_statuses), but for a live stream whose chat receives `messages`, and only
+	/// chat requests after the first page are answered with the statuses, so that the stream starts before failing.
+	pub async fn with_statuses_after_first_page(messages: Vec<&str>, statuses: Vec<(&str, &str)>) -> Self {
+		let statuses = statuses
+			.into_iter()
+			.map(|(prefix, status)| (prefix.to_owned(), status.to_owned()))
+			.collect();
+		Self::serve_route(MockRoute {
+			kind: MockStreamKind::Live,
+			messages: messages.into_iter().map(str::to_owned).collect(),
+			watch_page: watch_page(MockStreamKind::Live),
+			statuses,
+			statuses_from_page: 1,
+			tv: false
+		})
+		.await
+	}
+
+	/// Starts a server for a live stream whose chat receives `messages`, in order, that can only be reached through the
+	/// TV client: `/tv` is only served to the TV user agent, and chat requests must be sent as `TVHTML5`.
+	pub async fn with_tv_stream(messages: Vec<&str>) -> Self {
+		Self::serve_route(MockRoute {
+			kind: MockStreamKind::Live,
+			messages: messages.into_iter().map(str::to_owned).collect(),
+			watch_page: watch_page(MockStreamKind::Live),
+			statuses: Vec::new(),
+			statuses_from_page: 0,
+			tv: true
+		})
+		.await
+	}
+
+	async fn start(kind: MockStreamKind, messages: Vec<&str>) -> Self {
+		Self::serve(kind, messages.into_iter().map(str::to_owned).collect(), watch_page(kind)).await
+	}
+
+	async fn serve(kind: MockStreamKind, messages: Vec<String>, watch_page: String) -> Self {
+		Self::serve_route(MockRoute {
+			kind,
+			messages,
+			watch_page,
+			statuses: Vec::new(),
+			statuses_from_page: 0,
+			tv: false
+		})
+		.await
+	}
+
+	async fn serve_route(route: MockRoute) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+		let route = Arc::new(route);
+		tokio::spawn(async move {
+			while let Ok((socket, _)) = listener.accept().await {
+				let route = Arc::clone(&route);
+				tokio::spawn(async move {
+					let _ = handle_connection(socket, &route).await;
+				});
+			}
+		});
+		Self { url }
+	}
+
+	pub fn url(&self) -> &Url {
+		&self.url
+	}
+
+	/// Installs a client pool that sends all requests to this server.
+	///
+	/// The pool can only be installed once per process, so each test binary can only use one server.
+	pub fn install(&self) {
+		ClientPool::new(1, YouTubeConfig::new().with_base_url(self.url.clone()))
+			.install()
+			.expect("client pool already installed");
+	}
+}
+
+async fn handle_connection(mut socket: TcpStream, route: &MockRoute) -> std::io::Result<()> {
+	let mut request = Vec::new();
+	let mut buf = [0u8; 4096];
+	let header_end = loop {
+		let read = socket.read(&mut buf).await?;
+		if read == 0 {
+			return Ok(());
+		}
+		request.extend_from_slice(&buf[..read]);
+		if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+			break end + 4;
+		}
+	};
+
+	let head = String::from_utf8_lossy(&request[..header_end]).into_owned();
+	let content_length = head
+		.lines()
+		.find_map(|line| {
+			let (name, value) = line.split_once(':')?;
+			name.eq_ignore_ascii_case("content-length").then(|| value.trim().parse::<usize>().ok())?
+		})
+		.unwrap_or(0);
+	while request.len() < header_end + content_length {
+		let read = socket.read(&mut buf).await?;
+		if read == 0 {
+			break;
+		}
+		request.extend_from_slice(&buf[..read]);
+	}
+	let body = String::from_utf8_lossy(&request[header_end..]);
+
+	let path = head.split_whitespace().nth(1).unwrap_or("/");
+	let status = route.statuses.iter().find(|(prefix, _)| path.starts_with(prefix.as_str()));
+	if let Some((_, status)) = status.filter(|_| requested_page(&body).map_or(true, |page| page >= route.statuses_from_page)) {
+		return write_response(&mut socket, status, "text/plain", "").await;
+	}
+	// Only the one stream exists; watch pages for any other video are 404s, like deleted videos.
+	let (content_type, response) = if path.starts_with(&format!("/watch?v={}", MockYouTubeServer::VIDEO_ID)) {
+		("text/html", route.watch_page.clone())
+	} else if route.tv && path == "/tv" {
+		if !head.contains("Cobalt") {
+			return write_response(&mut socket, "400 Bad Request", "text/plain", "unsupported browser").await;
+		}
+		("text/html", include_str!("../fixtures/tv_page.html").to_owned())
+	} else if route.tv && path.starts_with("/youtubei/v1/next") {
+		("application/json", include_str!("../fixtures/tv_next.json").to_owned())
+	} else if path.starts_with("/youtubei/v1/live_chat/") {
+		if route.tv && !body.contains(r#""clientName":"TVHTML5""#) {
+			return write_response(&mut socket, "400 Bad Request", "text/plain", "expected a TVHTML5 client").await;
+		}
+		let page = requested_page(&body).unwrap_or(usize::MAX);
+		("application/json", simd_json::to_string(&chat_page(route.kind, &route.messages, page)).unwrap())
+	} else {
+		return write_response(&mut socket, "404 Not Found", "text/plain", "not found").await;
+	};
+	write_response(&mut socket, "200 OK", content_type, &response).await
+}
+
+async fn write_response(socket: &mut TcpStream, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+	let response =
+		format!("HTTP/1.1 {status}\r\nContent-Type: {content_type}; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+	socket.write_all(response.as_bytes()).await?;
+	socket.shutdown().await
+}
+
+/// Extracts the page number from the `page-N` continuation token in a chat request body.
+fn requested_page(body: &str) -> Option<usize> {
+	let start = body.find(r#""continuation":"page-"#)? + r#""continuation":"page-"#.len();
+	let end = start + body[start..].find('"')?;
+	body[start..end].parse().ok()
+}
+
+/// Builds a watch page with just enough of the inline config for `ChatContext::new_from_live` to pick up.
+fn watch_page(kind: MockStreamKind) -> String {
+	let (status, chat_title) = match kind {
+		MockStreamKind::Live => (r#""isLiveNow":true"#, "Live chat"),
+		MockStreamKind::Replay => (r#""isLiveNow":false,"isReplay":true"#, "Top chat replay")
+	};
+	format!(
+		r#"<!DOCTYPE html><html><body><script>
+ytcfg.set({{"INNERTUBE_API_KEY":"mock-api-key","INNERTUBE_CLIENT_VERSION":"2.20240101.00.00","clientVersion":"2.20240101.00.00"}});
+var ytInitialPlayerResponse = {{"videoDetails":{{"videoId":"{}","isLiveContent":true}},{status}}};
+var ytInitialData = {{"continuations":[{{"title":"{chat_title}","selected":true,"continuation":{{"reloadContinuationData":{{"continuation":"page-0"}}}}}}]}};
+</script></body></html>"#,
+		MockYouTubeServer::VIDEO_ID
+	)
+}
+
+fn chat_page(kind: MockStreamKind, messages: &[String], page: usize) -> OwnedValue {
+	let Some(message) = messages.get(page) else {
+		return json!({ "continuationContents": { "liveChatContinuation": { "continuations": [] } } });
+	};
+	let next = format!("page-{}", page + 1);
+	let action = text_message_action(page, message);
+	let (continuation, action) = match kind {
+		MockStreamKind::Live => (json!({ "timedContinuationData": { "timeoutMs": 10, "continuation": next } }), action),
+		MockStreamKind::Replay => (
+			json!({ "liveChatReplayContinuationData": { "timeUntilLastMessageMsec": 0, "continuation": next } }),
+			json!({
+				"replayChatItemAction": {
+					"actions": [action],
+					"videoOffsetTimeMsec": (page as u64 * 1000).to_string()
+				}
+			})
+		)
+	};
+	json!({
+		"continuationContents": {
+			"liveChatContinuation": {
+				"continuations": [continuation],
+				"actions": [action]
+			}
+		}
+	})
+}
+
+fn text_message_action(index: usize, text: &str) -> OwnedValue {
+	json!({
+		"addChatItemAction": {
+			"item": {
+				"liveChatTextMessageRenderer": {
+					"message": { "runs": [{ "text": text }] },
+					"authorName": { "simpleText": "Mock Viewer" },
+					"authorPhoto": { "thumbnails": [{ "url": "https://yt3.ggpht.com/mock", "width": 32, "height": 32 }] },
+					"contextMenuEndpoint": {
+						"commandMetadata": { "webCommandMetadata": { "ignoreNavigation": true } },
+						"liveChatItemContextMenuEndpoint": { "params": "mock" }
+					},
+					"id": format!("mock-message-{index}"),
+					"timestampUsec": (1_700_000_000_000_000u64 + index as u64 * 1_000_000).to_string(),
+					"authorExternalChannelId": "UCmockmockmockmockmock0A",
+					"contextMenuAccessibility": { "accessibilityData": { "label": "Chat actions" } }
+				}
+			},
+			"clientId": format!("mock-client-{index}")
+		}
+	})
+}
@@ -0,0 +1,45 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "audit")]
+
+use brainrot::youtube::{Action, ChatItem, ChatStreamExt};
+use futures_util::{stream, TryStreamExt};
+use sha2::{Digest, Sha256};
+use simd_json::prelude::*;
+
+#[tokio::test]
+async fn audit_log_hashes_each_record() -> anyhow::Result<()> {
+	let item = ChatItem::builder().text("hello").build();
+	let actions = stream::iter(vec![Ok(Action::AddChatItem { item, client_id: None })]);
+	let mut log = Vec::new();
+	let passed: Vec<Action> = actions.audit_log("mockstream1", &mut log).try_collect().await?;
+	assert_eq!(passed.len(), 1);
+
+	let mut line = log.strip_suffix(b"\n").expect("records are newline-terminated").to_vec();
+	let text = String::from_utf8(line.clone())?;
+	let parsed = simd_json::to_owned_value(&mut line)?;
+	let hash = parsed.get_str("sha256").expect("line has a sha256 field");
+	assert_eq!(parsed.get("record").and_then(|record| record.get_str("video_id")), Some("mockstream1"));
+	assert!(parsed.get("record").and_then(|record| record.get("logged_at")).is_some());
+
+	// The hash covers the `record` field's JSON exactly as written.
+	let record = text
+		.strip_prefix("{\"record\":")
+		.and_then(|rest| rest.strip_suffix(&format!(",\"sha256\":\"{hash}\"}}")))
+		.expect("line is `record` followed by `sha256`");
+	let expected: String = Sha256::digest(record.as_bytes()).iter().map(|byte| format!("{byte:02x}")).collect();
+	assert_eq!(hash, expected);
+	Ok(())
+}
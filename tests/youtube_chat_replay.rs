@@ -0,0 +1,46 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use std::time::Duration;
+
+use brainrot::youtube::{Action, ChatItem, ChatReplay};
+use chrono::Utc;
+use futures_util::StreamExt;
+use tokio::time::{timeout, Instant};
+
+fn replay_at(offset_ms: i64) -> Action {
+	Action::ReplayChat {
+		actions: Vec::new(),
+		video_offset_time_msec: offset_ms
+	}
+}
+
+#[tokio::test]
+async fn replay_paces_by_the_first_actions_clock() -> anyhow::Result<()> {
+	// A live chat item's timestamp is decades of epoch time, which would stall the replay if compared with the video
+	// offsets around it.
+	let item = ChatItem::builder().text("live").timestamp(Utc::now()).build();
+	let actions = vec![replay_at(0), Action::AddChatItem { item, client_id: None }, replay_at(100)];
+
+	let start = Instant::now();
+	let replayed: Vec<Action> = timeout(Duration::from_secs(5), ChatReplay::new(actions, 1.0).collect())
+		.await
+		.expect("replay stalled on an action from another clock");
+	assert_eq!(replayed.len(), 3);
+	assert!(matches!(replayed[1], Action::AddChatItem { .. }));
+	assert!(start.elapsed() >= Duration::from_millis(100));
+	Ok(())
+}
@@ -0,0 +1,23 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use brainrot::youtube::{ClientPool, YouTubeConfig};
+
+#[test]
+fn invalid_user_agent_does_not_panic() {
+	let pool = ClientPool::new(1, YouTubeConfig::new().with_user_agent("brainrot\r\nX-Injected: 1"));
+	pool.get();
+}
@@ -0,0 +1,81 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use brainrot::youtube::{Action, ChatItem, ChatStreamExt, DiscordFormat};
+use futures_util::stream;
+use simd_json::{base::ValueAsContainer, prelude::*};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::TcpListener,
+	sync::mpsc
+};
+use url::Url;
+
+/// Starts a webhook server that rate limits the first request, then accepts the rest, sending each request body it
+/// receives to the returned channel.
+async fn webhook_server() -> (Url, mpsc::UnboundedReceiver<String>) {
+	let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let url = Url::parse(&format!("http://{}/api/webhooks/1/token", listener.local_addr().unwrap())).unwrap();
+	let (tx, rx) = mpsc::unbounded_channel();
+	tokio::spawn(async move {
+		let mut requests = 0;
+		while let Ok((mut socket, _)) = listener.accept().await {
+			let mut request = Vec::new();
+			let mut buf = [0u8; 4096];
+			// Read until the whole JSON body has arrived; webhook payloads are one object.
+			while !request.ends_with(b"}") {
+				let read = socket.read(&mut buf).await.unwrap();
+				if read == 0 {
+					break;
+				}
+				request.extend_from_slice(&buf[..read]);
+			}
+			let request = String::from_utf8_lossy(&request).into_owned();
+			let body = request.split_once("\r\n\r\n").map(|(_, body)| body.to_owned()).unwrap_or_default();
+			requests += 1;
+			let response = if requests == 1 {
+				"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0.05\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+			} else {
+				"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n"
+			};
+			tx.send(body).unwrap();
+			socket.write_all(response.as_bytes()).await.unwrap();
+			socket.shutdown().await.unwrap();
+		}
+	});
+	(url, rx)
+}
+
+#[tokio::test]
+async fn webhook_retries_rate_limits_without_parsing_chat_mentions() -> anyhow::Result<()> {
+	let (url, mut bodies) = webhook_server().await;
+	let item = ChatItem::builder().text("hi <@&111> <@222> @everyone").build();
+	let actions = stream::iter(vec![Ok(Action::AddChatItem { item, client_id: None })]);
+	actions.forward_to_discord_webhook(url, DiscordFormat::default()).await?;
+
+	// The first attempt was rate limited, so the same payload is sent twice.
+	let first = bodies.recv().await.unwrap();
+	let mut retried = bodies.recv().await.unwrap();
+	assert_eq!(first, retried);
+
+	let payload = unsafe { simd_json::to_owned_value(retried.as_bytes_mut())? };
+	assert_eq!(payload["content"].as_str(), Some("hi <@&111> <@222> @everyone"));
+	let allowed_mentions = &payload["allowed_mentions"];
+	assert!(allowed_mentions["parse"].as_array().unwrap().is_empty());
+	assert!(allowed_mentions["roles"].as_array().unwrap().is_empty());
+	assert!(allowed_mentions["users"].as_array().unwrap().is_empty());
+	Ok(())
+}
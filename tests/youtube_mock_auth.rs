@@ -0,0 +1,35 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{ChatContext, Error};
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn unauthorized_and_forbidden_pages_require_auth() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_statuses(vec![("/watch?v=membersonly", "401 Unauthorized"), ("/watch?v=privatevid1", "403 Forbidden")]).await;
+	server.install();
+
+	let err = ChatContext::new_from_live("membersonly").await.unwrap_err();
+	assert!(matches!(err, Error::AuthRequired), "expected AuthRequired, got {err:?}");
+	assert!(err.is_auth_required());
+
+	let err = ChatContext::new_from_live("privatevid1").await.unwrap_err();
+	assert!(matches!(err, Error::BadStatus(status) if status == 403), "expected BadStatus(403), got {err:?}");
+	assert!(err.is_auth_required());
+	Ok(())
+}
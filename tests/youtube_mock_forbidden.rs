@@ -0,0 +1,53 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{self, Action, ChannelStatus, ChatContext, Error, StreamOptions};
+use futures_util::StreamExt;
+use mock::MockYouTubeServer;
+use reqwest::StatusCode;
+use tokio::sync::watch;
+
+#[tokio::test]
+async fn forbidden_chat_page_ends_stream() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_statuses_after_first_page(vec!["first", "second"], vec![("/youtubei/v1/live_chat/", "403 Forbidden")]).await;
+	server.install();
+
+	let context = ChatContext::new_from_live(MockYouTubeServer::VIDEO_ID).await?;
+	let (status_tx, status_rx) = watch::channel(ChannelStatus::Connecting);
+	let mut stream = youtube::stream_with_options(
+		&context,
+		StreamOptions {
+			signaler_status: Some(status_tx),
+			..Default::default()
+		}
+	)
+	.await?;
+
+	let Some(Ok(Action::AddChatItem { item, .. })) = stream.next().await else {
+		panic!("expected the first page's message")
+	};
+	assert_eq!(item.message().unwrap().to_chat_string(), "first");
+	// 403s won't go away by retrying, so the stream gives up straight away instead of using up its retries.
+	match stream.next().await {
+		Some(Err(Error::BadStatus(StatusCode::FORBIDDEN))) => {}
+		other => panic!("expected a 403 error, got {other:?}")
+	}
+	assert!(stream.next().await.is_none());
+	assert!(matches!(*status_rx.borrow(), ChannelStatus::Failed(Error::BadStatus(StatusCode::FORBIDDEN))));
+	Ok(())
+}
@@ -0,0 +1,43 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{self, Action, ChatContext, LiveStreamStatus};
+use futures_util::TryStreamExt;
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn streams_live_chat_until_broadcast_ends() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_live_stream(vec!["first", "second", "third"]).await;
+	server.install();
+
+	let context = ChatContext::new_from_live(MockYouTubeServer::VIDEO_ID).await?;
+	assert_eq!(context.status(), LiveStreamStatus::Live);
+
+	let messages: Vec<String> = youtube::stream(&context)
+		.await?
+		.try_filter_map(|action| async move {
+			Ok(match action {
+				Action::AddChatItem { item, .. } => item.message().map(|message| message.to_chat_string()),
+				_ => None
+			})
+		})
+		.try_collect()
+		.await?;
+	assert_eq!(messages, ["first", "second", "third"]);
+	Ok(())
+}
@@ -0,0 +1,30 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{ChatContext, Error};
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn deleted_video_is_page_not_found() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_live_stream(vec![]).await;
+	server.install();
+
+	let err = ChatContext::new_from_live("deletedvid1").await.unwrap_err();
+	assert!(matches!(&err, Error::PageNotFound(id) if id == "deletedvid1"), "expected PageNotFound, got {err:?}");
+	Ok(())
+}
@@ -0,0 +1,34 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{self, ChatContext, Error};
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn rate_limited_chat_request_errors() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_statuses(vec![("/youtubei/v1/live_chat/", "429 Too Many Requests")]).await;
+	server.install();
+
+	let context = ChatContext::new_from_live(MockYouTubeServer::VIDEO_ID).await?;
+	let Err(err) = youtube::stream(&context).await else {
+		panic!("expected the first chat request to fail")
+	};
+	assert!(matches!(err, Error::RateLimited), "expected RateLimited, got {err:?}");
+	assert!(err.is_retryable());
+	Ok(())
+}
@@ -0,0 +1,60 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use std::time::Duration;
+
+use brainrot::youtube::{self, Action, ActionChunk, ChatContext, ChatStreamExt, LiveStreamStatus, StreamOptions};
+use futures_util::TryStreamExt;
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn streams_chat_replay() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_replay(vec!["first", "second"]).await;
+	server.install();
+
+	let context = ChatContext::new_from_live(MockYouTubeServer::VIDEO_ID).await?;
+	assert_eq!(context.status(), LiveStreamStatus::Replay);
+
+	let actions: Vec<Action> = youtube::stream(&context).await?.try_collect().await?;
+	let messages: Vec<String> = actions
+		.iter()
+		.filter_map(|action| match action {
+			Action::AddChatItem { item, .. } => item.message().map(|message| message.to_chat_string()),
+			_ => None
+		})
+		.collect();
+	assert_eq!(messages, ["first", "second"]);
+
+	// Chunks flatten replay actions out of their wrappers unless the stream asks for offsets.
+	let chunk = ActionChunk::fetch(&context).await?;
+	assert_eq!(chunk.len(), 1);
+	assert!(chunk.into_iter().all(|action| matches!(action, Action::AddChatItem { .. })));
+
+	let stream_options = StreamOptions {
+		replay_offsets: true,
+		..Default::default()
+	};
+	let offsets: Vec<Duration> = youtube::stream_with_options(&context, stream_options)
+		.await?
+		.zip_timestamps()
+		.map_ok(|(offset, _)| offset)
+		.try_collect()
+		.await?;
+	assert_eq!(offsets, [Duration::ZERO, Duration::from_secs(1)]);
+	Ok(())
+}
@@ -0,0 +1,46 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+mod mock;
+
+use brainrot::youtube::{self, Action, ChatContext, ClientPlatform, LiveStreamStatus};
+use futures_util::TryStreamExt;
+use mock::MockYouTubeServer;
+
+#[tokio::test]
+async fn streams_live_chat_as_tv_client() -> anyhow::Result<()> {
+	let server = MockYouTubeServer::with_tv_stream(vec!["hello from the couch", "second"]).await;
+	server.install();
+
+	let context = ChatContext::new_from_tv(MockYouTubeServer::VIDEO_ID).await?;
+	assert_eq!(context.status(), LiveStreamStatus::Live);
+	assert_eq!(context.client_platform(), ClientPlatform::Tv);
+	assert_eq!(context.api_key(), "mock-tv-api-key");
+	assert_eq!(context.client_version(), "7.20240101.10.00");
+	assert_eq!(context.initial_continuation(), "page-0");
+
+	// The mock only answers chat requests sent as the TV client.
+	let actions: Vec<Action> = youtube::stream(&context).await?.try_collect().await?;
+	let messages: Vec<String> = actions
+		.iter()
+		.filter_map(|action| match action {
+			Action::AddChatItem { item, .. } => item.message().map(|message| message.to_chat_string()),
+			_ => None
+		})
+		.collect();
+	assert_eq!(messages, ["hello from the couch", "second"]);
+	Ok(())
+}
@@ -0,0 +1,112 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use std::path::PathBuf;
+
+use brainrot::youtube::{replay_from_file, Action, ChatContext, ChatStreamExt, ClientPool, YouTubeConfig};
+use futures_util::TryStreamExt;
+
+const PAGES: [&str; 5] = [
+	include_str!("fixtures/replay/page_1.json"),
+	include_str!("fixtures/replay/page_2.json"),
+	include_str!("fixtures/replay/page_3.json"),
+	include_str!("fixtures/replay/page_4.json"),
+	include_str!("fixtures/replay/page_5.json")
+];
+
+/// Writes the fixture pages to a temporary recording, as `record_to_file` would, and returns its path.
+async fn write_recording(name: &str) -> anyhow::Result<PathBuf> {
+	// `record_to_file` writes one response per line, so minify each page.
+	let mut recording = String::new();
+	for page in PAGES {
+		let value = unsafe { simd_json::to_owned_value(page.to_owned().as_bytes_mut())? };
+		recording.push_str(&simd_json::to_string(&value)?);
+		recording.push('\n');
+	}
+	let path = std::env::temp_dir().join(format!("brainrot-{name}-{}.jsonl", std::process::id()));
+	tokio::fs::write(&path, recording).await?;
+	Ok(path)
+}
+
+#[tokio::test]
+async fn replay_from_file_yields_all_messages_in_order() -> anyhow::Result<()> {
+	let path = write_recording("replay").await?;
+
+	// The default context is a replay, which is all `replay_from_file` needs.
+	let context = ChatContext::default();
+	let actions: Result<Vec<Action>, _> = replay_from_file(&path, &context).try_collect().await;
+	tokio::fs::remove_file(&path).await?;
+	// The last page has no continuation; that should end the stream, not surface as an error.
+	let actions = actions?;
+
+	let items: Vec<_> = actions
+		.iter()
+		.map(|action| match action {
+			Action::AddChatItem { item, .. } => item,
+			action => panic!("expected only chat items, got {action:?}")
+		})
+		.collect();
+	assert_eq!(items.len(), 8);
+	assert_eq!(items[0].message().unwrap().to_chat_string(), "first!");
+	assert_eq!(items[7].message().unwrap().to_chat_string(), "see you next stream");
+	assert!(items.windows(2).all(|pair| pair[0].timestamp() < pair[1].timestamp()));
+	Ok(())
+}
+
+#[tokio::test]
+async fn try_count_counts_replayed_actions() -> anyhow::Result<()> {
+	let path = write_recording("count").await?;
+	let context = ChatContext::default();
+	let count = replay_from_file(&path, &context).try_count().await;
+	tokio::fs::remove_file(&path).await?;
+	assert_eq!(count?, 8);
+	Ok(())
+}
+
+#[tokio::test]
+async fn first_n_stops_after_n_actions() -> anyhow::Result<()> {
+	let path = write_recording("first-n").await?;
+	let context = ChatContext::default();
+	let first = replay_from_file(&path, &context).first_n(3).await;
+	let all = replay_from_file(&path, &context).first_n(100).await;
+	tokio::fs::remove_file(&path).await?;
+	let (first, all) = (first?, all?);
+	assert_eq!(first.len(), 3);
+	assert_eq!(all.len(), 8);
+	let ids = |actions: &[Action]| -> Vec<String> {
+		actions
+			.iter()
+			.map(|action| match action {
+				Action::AddChatItem { item, .. } => item.id().to_owned(),
+				action => panic!("expected only chat items, got {action:?}")
+			})
+			.collect()
+	};
+	assert_eq!(ids(&first), ids(&all)[..3]);
+	Ok(())
+}
+
+#[tokio::test]
+async fn replaying_does_not_initialize_client_pool() -> anyhow::Result<()> {
+	let path = write_recording("no-pool").await?;
+	let context = ChatContext::default();
+	let count = replay_from_file(&path, &context).try_count().await;
+	tokio::fs::remove_file(&path).await?;
+	assert_eq!(count?, 8);
+	// Parsing pages shouldn't have created the default pool, so one can still be installed.
+	assert!(ClientPool::new(1, YouTubeConfig::new()).install().is_ok());
+	Ok(())
+}
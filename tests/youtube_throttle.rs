@@ -0,0 +1,49 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use std::time::Duration;
+
+use brainrot::youtube::ChatStreamExt;
+use futures_util::{stream, StreamExt};
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn throttle_drops_ready_items_past_buffer_size() {
+	let throttled = stream::iter(0..1000).throttle(1000, Duration::from_secs(1)).with_buffer_size(10);
+	let items: Vec<u32> = timeout(Duration::from_secs(5), throttled.collect()).await.expect("throttle stalled");
+	// The first full buffer comes through intact; after that, only what fits in the space freed by each item yielded.
+	assert_eq!(items[..10], (0..10).collect::<Vec<_>>());
+	assert!(items.len() < 1000, "expected items past the buffer size to be dropped");
+	assert!(items.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[tokio::test]
+async fn throttle_with_backpressure_keeps_every_item() {
+	let throttled = stream::iter(0..1000)
+		.throttle(1000, Duration::from_secs(1))
+		.with_buffer_size(10)
+		.with_backpressure();
+	let items: Vec<u32> = timeout(Duration::from_secs(5), throttled.collect()).await.expect("throttle stalled");
+	assert_eq!(items, (0..1000).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn throttle_yields_from_endless_ready_stream() {
+	let items: Vec<u32> = timeout(Duration::from_secs(5), stream::repeat(1).throttle(5, Duration::from_secs(1)).take(5).collect())
+		.await
+		.expect("throttle stalled");
+	assert_eq!(items, [1; 5]);
+}
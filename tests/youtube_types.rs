@@ -0,0 +1,191 @@
+// Copyright 2024 pyke.io
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "youtube")]
+
+use brainrot::youtube::{superchat_tier_from_amount, Action, Author, Badge, ChatEvent, ChatItem, MessageReply, MessageSource, SuperchatInfo};
+
+#[test]
+fn unknown_renderer_does_not_panic() {
+	let mut json = br#"{"addChatItemAction":{"item":{"liveChatSomeFutureRenderer":{"id":"abc","text":"hi"}},"clientId":"x"}}"#.to_vec();
+	let action: Action = simd_json::from_slice(&mut json).unwrap();
+	let Action::AddChatItem { item, .. } = action else {
+		panic!("expected AddChatItem, got {action:?}")
+	};
+	assert!(matches!(item, ChatItem::Unknown(_)));
+	assert!(item.message().is_none());
+	assert!(item.message_renderer_base().is_none());
+	assert!(item.timestamp().is_none());
+}
+
+#[test]
+fn unknown_action_does_not_panic() {
+	let mut json = br#"{"someFutureAction":{"data":[1,2,3]}}"#.to_vec();
+	let action: Action = simd_json::from_slice(&mut json).unwrap();
+	assert!(matches!(action, Action::Unknown(_)));
+
+	// A known action that's missing required fields becomes unknown too, rather than failing the whole page.
+	let mut json = br#"{"removeChatItemAction":{"unexpected":true}}"#.to_vec();
+	let action: Action = simd_json::from_slice(&mut json).unwrap();
+	assert!(matches!(action, Action::Unknown(_)));
+}
+
+#[test]
+fn auto_mod_message_wraps_held_item() {
+	let mut json = br#"{"addChatItemAction":{"item":{"liveChatAutoModMessageRenderer":{
+		"id":"held",
+		"autoModeratedItem":{"liveChatViewerEngagementMessageRenderer":{"id":"inner"}},
+		"headerText":{"runs":[{"text":"Held for review"}]}
+	}}}}"#
+		.to_vec();
+	let action: Action = simd_json::from_slice(&mut json).unwrap();
+	let Some(ChatEvent::AutoModHeld { id, inner, reason }) = ChatEvent::from_action(action) else {
+		panic!("expected an AutoModHeld event")
+	};
+	assert_eq!(id, "held");
+	assert_eq!(inner.id(), "inner");
+	assert_eq!(reason.as_deref(), Some("Held for review"));
+}
+
+#[test]
+fn builder_builds_superchat() {
+	let author = Author::builder()
+		.id("UC123")
+		.display_name("someone")
+		.avatar_url("https://yt4.ggpht.com/a=s64")
+		.badge(Badge::Moderator)
+		.build();
+	let tier = superchat_tier_from_amount(5_000_000, "USD").unwrap();
+	let item = ChatItem::builder()
+		.author(author)
+		.text("thanks for the stream")
+		.timestamp_ms(1_700_000_000_123)
+		.superchat(SuperchatInfo::new("$5.00", tier))
+		.build();
+	assert!(matches!(item, ChatItem::Superchat { .. }));
+	assert!(item.validate().is_ok());
+	assert_eq!(item.timestamp().unwrap().timestamp_millis(), 1_700_000_000_123);
+	assert_eq!(item.message().unwrap().to_chat_string(), "thanks for the stream");
+	let author = item.author().unwrap();
+	assert_eq!(author.id, "UC123");
+	assert_eq!(author.name.as_deref(), Some("someone"));
+	assert_eq!(author.avatar_image().unwrap().url(), "https://yt4.ggpht.com/a=s64");
+	assert!(matches!(author.badges[..], [Badge::Moderator]));
+	assert_ne!(ChatItem::builder().build().id(), ChatItem::builder().build().id());
+}
+
+#[test]
+fn text_with_source_falls_back_when_message_is_missing() {
+	let item = ChatItem::builder().build();
+	assert!(matches!(item, ChatItem::TextMessage { message: None, .. }));
+	assert!(item.text_with_source().is_none());
+
+	let tier = superchat_tier_from_amount(5_000_000, "USD").unwrap();
+	let header = ChatItem::builder().text("header").build().message().cloned();
+	let mut item = ChatItem::builder().text("body").superchat(SuperchatInfo::new("$5.00", tier)).build();
+	let ChatItem::Superchat { header_sub_text, .. } = &mut item else {
+		panic!("expected a Superchat, got {item:?}")
+	};
+	*header_sub_text = header;
+	let (source, text) = item.text_with_source().unwrap();
+	assert_eq!((source, text.to_chat_string().as_str()), (MessageSource::Message, "body"));
+
+	if let ChatItem::Superchat { message, .. } = &mut item {
+		*message = None;
+	}
+	let (source, text) = item.text_with_source().unwrap();
+	assert_eq!((source, text.to_chat_string().as_str()), (MessageSource::HeaderSubtext, "header"));
+}
+
+fn chat_item(fixture: &str) -> ChatItem {
+	let action: Action = simd_json::from_slice(&mut fixture.as_bytes().to_vec()).unwrap();
+	let Action::AddChatItem { item, .. } = action else {
+		panic!("expected AddChatItem, got {action:?}")
+	};
+	item
+}
+
+#[test]
+fn reply_to_reads_reply_button_and_context_menu() {
+	let replied_to = Some(MessageReply {
+		message_id: "ChwKGkNLbXJfNXZTaDRRREZSd0twd1FkT3A4S00".to_owned(),
+		author_id: "UCq3Fz8mLw2RpK7vNt4YbX1A".to_owned()
+	});
+	assert_eq!(chat_item(include_str!("fixtures/reply/reply_button.json")).reply_to(), replied_to);
+	assert_eq!(chat_item(include_str!("fixtures/reply/context_menu_reply.json")).reply_to(), replied_to);
+
+	// Messages that aren't replies have neither field.
+	let page: simd_json::OwnedValue = simd_json::from_slice(&mut include_bytes!("fixtures/replay/page_1.json").to_vec()).unwrap();
+	let action = &page["continuationContents"]["liveChatContinuation"]["actions"][0]["replayChatItemAction"]["actions"][0];
+	assert!(chat_item(&simd_json::to_string(action).unwrap()).reply_to().is_none());
+	assert!(ChatItem::builder().text("hi").build().reply_to().is_none());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn chat_item_round_trips() {
+	let json = r#"{"liveChatTextMessageRenderer":{"message":{"runs":[{"text":"hello"}]},"authorName":{"simpleText":"someone"},"authorPhoto":{"thumbnails":[{"url":"https://yt4.ggpht.com/a=s64","width":64,"height":64}]},"contextMenuEndpoint":{"commandMetadata":{"webCommandMetadata":{"ignoreNavigation":true}},"liveChatItemContextMenuEndpoint":{"params":"p"}},"id":"abc","timestampUsec":"1700000000123456","authorExternalChannelId":"UC123","contextMenuAccessibility":{"accessibilityData":{"label":"Chat actions"}}}}"#;
+	let item = brainrot::youtube::chat_item_from_json(json).unwrap();
+	let serialized = simd_json::to_string(&item).unwrap();
+	let round_tripped = brainrot::youtube::chat_item_from_json(&serialized).unwrap();
+	assert_eq!(round_tripped.id(), "abc");
+	assert_eq!(round_tripped.timestamp(), item.timestamp());
+	assert_eq!(round_tripped.message().map(|message| message.to_chat_string()).as_deref(), Some("hello"));
+	assert_eq!(round_tripped.author().map(|author| author.id).as_deref(), Some("UC123"));
+}
+
+#[cfg(feature = "proto")]
+#[test]
+fn proto_chat_message_round_trips() {
+	use brainrot::youtube::proto::{
+		self,
+		message_run::{Emoji, Run}
+	};
+	use prost::Message;
+
+	let message = proto::ChatMessage {
+		id: "abc".to_string(),
+		author: Some(proto::Author {
+			id: "UC123".to_string(),
+			name: Some("someone".to_string()),
+			avatar_url: Some("https://yt4.ggpht.com/a=s64".to_string()),
+			badges: vec![proto::Badge {
+				kind: proto::badge::Kind::Member as i32,
+				tooltip: Some("Member (6 months)".to_string())
+			}]
+		}),
+		timestamp_usec: 1_700_000_000_123_456,
+		runs: vec![
+			proto::MessageRun {
+				run: Some(Run::Text("hi ".to_string()))
+			},
+			proto::MessageRun {
+				run: Some(Run::Emoji(Emoji {
+					id: "UCkszU2WH9gy1mb0dV-11UJg/wave".to_string(),
+					label: "wave".to_string(),
+					is_custom: true,
+					image_url: Some("https://yt3.ggpht.com/wave".to_string())
+				}))
+			},
+		],
+		purchase_amount: Some("$5.00".to_string())
+	};
+	let decoded = proto::ChatMessage::decode(&message.encode_to_vec()[..]).unwrap();
+	assert_eq!(decoded, message);
+
+	let item = ChatItem::from(decoded);
+	assert!(matches!(item, ChatItem::Superchat { .. }));
+	assert_eq!(item.message().unwrap().to_chat_string(), "hi :wave:");
+	assert_eq!(proto::ChatMessage::from(item), message);
+}